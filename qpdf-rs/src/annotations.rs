@@ -0,0 +1,181 @@
+use crate::{
+    content::format_number, page_boxes::rect_from_object, ContentBuilder, QPdf, QPdfArray, QPdfDictionary,
+    QPdfObjectLike, QPdfObjectType, QPdfScalar, QPdfStream, Result,
+};
+
+// Annotation flags which make the annotation invisible
+const FLAG_HIDDEN: i64 = 1 << 1;
+const FLAG_NO_VIEW: i64 = 1 << 5;
+
+impl QPdf {
+    /// Bake the annotation appearances into the page contents and remove the baked annotations from `/Annots`.
+    ///
+    /// The normal appearance stream of every annotation (selected by `/AS` when there are several states)
+    /// is drawn with its `/Matrix` and `/BBox` mapped to the annotation `/Rect`.
+    /// `/Link` annotations without an appearance are drawn as a rectangle only if they have an explicit
+    /// visible border in `/BS` or `/Border`, the links which are not baked stay in `/Annots` to keep
+    /// their clickable areas. Form field widgets are not baked and stay in `/Annots`, so the fields of
+    /// the catalog `/AcroForm` remain on the pages. Other hidden annotations and annotations without
+    /// an appearance are dropped. The `/Annots` array is removed when no annotations remain.
+    pub fn flatten_annotations(&self) -> Result<()> {
+        for page in self.get_pages()? {
+            let annots = match page.get("/Annots") {
+                Some(annots) if annots.is_array() => QPdfArray::from(annots),
+                _ => continue,
+            };
+
+            let mut content = Vec::new();
+            let mut kept = Vec::new();
+            for annot in annots.iter() {
+                if !annot.is_dictionary() {
+                    continue;
+                }
+                let dict = QPdfDictionary::from(annot.clone());
+                let subtype = dict.get("/Subtype").map(|s| s.as_name());
+                if subtype.as_deref() == Some("/Widget") {
+                    kept.push(annot);
+                    continue;
+                }
+                let is_link = subtype.as_deref() == Some("/Link");
+
+                let flags = dict
+                    .get("/F")
+                    .filter(|flags| flags.is_integer())
+                    .map(|flags| QPdfScalar::from(flags).as_i64())
+                    .unwrap_or_default();
+                let rect = match rect_from_object(dict.get("/Rect")) {
+                    Some(rect) if flags & (FLAG_HIDDEN | FLAG_NO_VIEW) == 0 => rect,
+                    _ => {
+                        if is_link {
+                            kept.push(annot);
+                        }
+                        continue;
+                    }
+                };
+
+                if let Some(appearance) = normal_appearance(&dict) {
+                    let name = self.add_form_resource(&page, appearance.as_object());
+                    let matrix = appearance_matrix(&appearance.get_dictionary(), rect)
+                        .iter()
+                        .map(|v| format_number(*v))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    content.extend_from_slice(format!("q\n{} cm\n{} Do\nQ\n", matrix, name).as_bytes());
+                } else if is_link {
+                    match link_border(&dict, rect) {
+                        Some(border) => content.extend_from_slice(border.as_bytes()),
+                        None => kept.push(annot),
+                    }
+                }
+            }
+
+            if !content.is_empty() {
+                self.append_page_content(&page, content, false)?;
+            }
+            if kept.is_empty() {
+                page.remove("/Annots");
+            } else {
+                page.set("/Annots", self.new_array_from(kept));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Select the normal appearance stream, using the appearance state for the appearance subdictionaries
+fn normal_appearance(annot: &QPdfDictionary) -> Option<QPdfStream> {
+    let appearance = annot
+        .get("/AP")
+        .filter(|ap| ap.is_dictionary())
+        .and_then(|ap| QPdfDictionary::from(ap).get("/N"))?;
+    let stream = match appearance.get_type() {
+        QPdfObjectType::Stream => appearance,
+        QPdfObjectType::Dictionary => {
+            let state = annot.get("/AS").map(|state| state.as_name())?;
+            QPdfDictionary::from(appearance)
+                .get(&state)
+                .filter(|stream| stream.is_stream())?
+        }
+        _ => return None,
+    };
+    Some(stream.into())
+}
+
+// Compute the matrix which maps the transformed appearance bounding box to the annotation rectangle
+fn appearance_matrix(dict: &QPdfDictionary, rect: [f64; 4]) -> [f64; 6] {
+    let bbox = rect_from_object(dict.get("/BBox")).unwrap_or(rect);
+    let matrix = dict
+        .get("/Matrix")
+        .filter(|matrix| matrix.is_array())
+        .map(QPdfArray::from)
+        .and_then(|matrix| matrix.as_f64_vec()?.try_into().ok())
+        .unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    let corners = [
+        (bbox[0], bbox[1]),
+        (bbox[0], bbox[3]),
+        (bbox[2], bbox[1]),
+        (bbox[2], bbox[3]),
+    ]
+    .map(|(x, y)| {
+        (
+            matrix[0] * x + matrix[2] * y + matrix[4],
+            matrix[1] * x + matrix[3] * y + matrix[5],
+        )
+    });
+    let (x0, x1) = min_max(corners.iter().map(|c| c.0));
+    let (y0, y1) = min_max(corners.iter().map(|c| c.1));
+    let (rx0, rx1) = min_max([rect[0], rect[2]].into_iter());
+    let (ry0, ry1) = min_max([rect[1], rect[3]].into_iter());
+
+    let sx = if x1 > x0 { (rx1 - rx0) / (x1 - x0) } else { 1.0 };
+    let sy = if y1 > y0 { (ry1 - ry0) / (y1 - y0) } else { 1.0 };
+    [sx, 0.0, 0.0, sy, rx0 - x0 * sx, ry0 - y0 * sy]
+}
+
+fn min_max<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+        (min.min(v), max.max(v))
+    })
+}
+
+// Draw the link border if it is explicitly visible
+fn link_border(annot: &QPdfDictionary, rect: [f64; 4]) -> Option<String> {
+    let width = annot
+        .get("/BS")
+        .filter(|bs| bs.is_dictionary())
+        .and_then(|bs| QPdfDictionary::from(bs).get("/W"))
+        .or_else(|| {
+            annot
+                .get("/Border")
+                .filter(|border| border.is_array())
+                .and_then(|border| QPdfArray::from(border).get(2))
+        })
+        .and_then(|width| width.as_number())
+        .filter(|width| *width > 0.0)?;
+
+    // the color defaults to black, an empty color array means a transparent border
+    let color = annot
+        .get("/C")
+        .filter(|color| color.is_array())
+        .and_then(|color| QPdfArray::from(color).as_f64_vec())
+        .unwrap_or_else(|| vec![0.0]);
+
+    let mut builder = ContentBuilder::new();
+    builder.set_line_width(width);
+    match color.as_slice() {
+        [gray] => builder.set_rgb_stroke(*gray, *gray, *gray),
+        [r, g, b] => builder.set_rgb_stroke(*r, *g, *b),
+        [c, m, y, k] => builder.set_rgb_stroke((1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)),
+        _ => return None,
+    };
+    let half = width / 2.0;
+    let (x0, x1) = min_max([rect[0], rect[2]].into_iter());
+    let (y0, y1) = min_max([rect[1], rect[3]].into_iter());
+    builder
+        .rectangle(x0 + half, y0 + half, x1 - x0 - width, y1 - y0 - width)
+        .stroke();
+
+    Some(format!("q\n{}Q\n", String::from_utf8_lossy(builder.as_bytes())))
+}
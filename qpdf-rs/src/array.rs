@@ -72,6 +72,18 @@ impl QPdfArray {
         }
     }
 
+    /// Convert the array of numbers into a vector, return `None` if any element is not a number
+    pub fn as_f64_vec(&self) -> Option<Vec<f64>> {
+        self.iter().map(|item| item.as_number()).collect()
+    }
+
+    /// Convert the array of names into a vector, return `None` if any element is not a name
+    pub fn as_name_vec(&self) -> Option<Vec<String>> {
+        self.iter()
+            .map(|item| (item.is_name()).then(|| item.as_name()))
+            .collect()
+    }
+
     /// Remove array item
     pub fn remove(&mut self, index: usize) {
         unsafe {
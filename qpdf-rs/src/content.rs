@@ -0,0 +1,305 @@
+use crate::{
+    text::decode_string,
+    tokenizer::{QPdfToken, QPdfTokenType, QPdfTokenizer},
+    QPdf, QPdfArray, QPdfDictionary, QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike, Result,
+};
+
+/// Builder for the page content stream data.
+///
+/// Every method appends a single operator with its operands on a separate line,
+/// the resulting bytes can be passed to [`QPdf::new_stream`](crate::QPdf::new_stream).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentBuilder {
+    data: Vec<u8>,
+}
+
+impl ContentBuilder {
+    /// Create an empty content builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin a text object (`BT`)
+    pub fn begin_text(&mut self) -> &mut Self {
+        self.operator(&[], "BT")
+    }
+
+    /// End a text object (`ET`)
+    pub fn end_text(&mut self) -> &mut Self {
+        self.operator(&[], "ET")
+    }
+
+    /// Set the font resource name and size (`Tf`), the name may be given with or without the leading slash
+    pub fn set_font(&mut self, name: &str, size: f64) -> &mut Self {
+        let name = if name.starts_with('/') {
+            name.to_owned()
+        } else {
+            format!("/{}", name)
+        };
+        self.operator(&[name, format_number(size)], "Tf")
+    }
+
+    /// Move to the start of the next line offset by `tx` and `ty` (`Td`)
+    pub fn move_text(&mut self, tx: f64, ty: f64) -> &mut Self {
+        self.operator(&[format_number(tx), format_number(ty)], "Td")
+    }
+
+    /// Show a text string (`Tj`). The text bytes are written as a literal string,
+    /// parentheses, backslashes and line breaks are escaped.
+    pub fn show_text<T: AsRef<[u8]>>(&mut self, text: T) -> &mut Self {
+        write_literal_string(&mut self.data, text.as_ref());
+        self.data.push(b' ');
+        self.operator(&[], "Tj")
+    }
+
+    /// Append a rectangle to the current path (`re`)
+    pub fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64) -> &mut Self {
+        let operands = [x, y, width, height].map(format_number);
+        self.operator(&operands, "re")
+    }
+
+    /// Fill the current path using the nonzero winding number rule (`f`)
+    pub fn fill(&mut self) -> &mut Self {
+        self.operator(&[], "f")
+    }
+
+    /// Stroke the current path (`S`)
+    pub fn stroke(&mut self) -> &mut Self {
+        self.operator(&[], "S")
+    }
+
+    /// Set the line width for the stroke operations (`w`)
+    pub fn set_line_width(&mut self, width: f64) -> &mut Self {
+        self.operator(&[format_number(width)], "w")
+    }
+
+    /// Set the RGB color for the fill operations (`rg`), components are in the range 0.0 to 1.0
+    pub fn set_rgb(&mut self, r: f64, g: f64, b: f64) -> &mut Self {
+        let operands = [r, g, b].map(format_number);
+        self.operator(&operands, "rg")
+    }
+
+    /// Set the RGB color for the stroke operations (`RG`), components are in the range 0.0 to 1.0
+    pub fn set_rgb_stroke(&mut self, r: f64, g: f64, b: f64) -> &mut Self {
+        let operands = [r, g, b].map(format_number);
+        self.operator(&operands, "RG")
+    }
+
+    /// Get the content stream data built so far
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the builder and return the content stream data
+    pub fn build(self) -> Vec<u8> {
+        self.data
+    }
+
+    fn operator(&mut self, operands: &[String], operator: &str) -> &mut Self {
+        for operand in operands {
+            self.data.extend_from_slice(operand.as_bytes());
+            self.data.push(b' ');
+        }
+        self.data.extend_from_slice(operator.as_bytes());
+        self.data.push(b'\n');
+        self
+    }
+}
+
+/// Content stream operator with its operands
+#[derive(Debug, Clone)]
+pub struct ContentOperator {
+    /// Operator name, for example `Tf`
+    pub operator: String,
+    /// Direct objects for the operands in the stream order
+    pub operands: Vec<QPdfObject>,
+}
+
+impl QPdf {
+    /// Parse the content stream data into the list of operators with their operands.
+    ///
+    /// An inline image is returned as a single `BI` operator with two operands: the image dictionary
+    /// with the keys as they appear in the stream, and the image data as a binary string.
+    /// Malformed tokens and operands without the following operator result in `QPdfErrorCode::DamagedPdf`.
+    pub fn parse_content(&self, data: &[u8]) -> Result<Vec<ContentOperator>> {
+        let mut result = Vec::new();
+        let mut operands = Vec::new();
+        let mut tokens = QPdfTokenizer::new(data);
+
+        while let Some(token) = tokens.next() {
+            match token.token_type() {
+                QPdfTokenType::Bad => {
+                    return Err(damaged_content(format!(
+                        "Invalid token in content: {}",
+                        String::from_utf8_lossy(token.raw())
+                    )))
+                }
+                QPdfTokenType::Operator if token.raw() == b"BI" => {
+                    let dict_tokens = tokens
+                        .by_ref()
+                        .take_while(|token| token.token_type() != QPdfTokenType::Operator || token.raw() != b"ID")
+                        .collect::<Vec<_>>();
+                    let image_data = match tokens.next() {
+                        Some(token) if token.token_type() == QPdfTokenType::InlineImage => token,
+                        _ => return Err(damaged_content("Unterminated inline image".to_owned())),
+                    };
+                    let dictionary = self.parse_object(&format!("<< {} >>", operand_text(&dict_tokens)))?;
+                    result.push(ContentOperator {
+                        operator: "BI".to_owned(),
+                        operands: vec![dictionary, self.new_binary_string(image_data.raw())],
+                    });
+                    // the EI operator which follows the image data
+                    tokens.next();
+                }
+                QPdfTokenType::Operator => {
+                    let array: QPdfArray = self.parse_object(&format!("[ {} ]", operand_text(&operands)))?.into();
+                    result.push(ContentOperator {
+                        operator: String::from_utf8_lossy(token.raw()).into_owned(),
+                        operands: array.iter().collect(),
+                    });
+                    operands.clear();
+                }
+                _ => operands.push(token),
+            }
+        }
+
+        if !operands.is_empty() {
+            return Err(damaged_content(
+                "Operands without operator at the end of content".to_owned(),
+            ));
+        }
+
+        Ok(result)
+    }
+}
+
+impl ContentOperator {
+    // Append the operator with its operands as a single line of the content stream
+    fn write_to(&self, data: &mut Vec<u8>) {
+        if self.operator == "BI" {
+            if let [dictionary, image_data] = self.operands.as_slice() {
+                data.extend_from_slice(b"BI");
+                if dictionary.is_dictionary() {
+                    write_dictionary_entries(data, &dictionary.clone().into());
+                }
+                data.extend_from_slice(b" ID ");
+                data.extend_from_slice(&image_data.as_binary_string());
+                data.extend_from_slice(b"\nEI\n");
+                return;
+            }
+        }
+        for operand in &self.operands {
+            write_operand(data, operand);
+            data.push(b' ');
+        }
+        data.extend_from_slice(self.operator.as_bytes());
+        data.push(b'\n');
+    }
+}
+
+impl QPdf {
+    /// Rewrite the page content by passing each operator to `f`, which returns the operators to write
+    /// in its place: an empty vector to drop it, the operator itself to keep it, or several operators.
+    /// The content is parsed with `parse_content` and the page `/Contents` is replaced with a single
+    /// new stream. Resources referenced by the new operators must be added to the page separately.
+    pub fn rewrite_page_content<F>(&self, page: &QPdfDictionary, mut f: F) -> Result<()>
+    where
+        F: FnMut(ContentOperator) -> Vec<ContentOperator>,
+    {
+        let operators = self.parse_content(&page.get_page_content_data()?)?;
+        let mut data = Vec::new();
+        for operator in operators {
+            for operator in f(operator) {
+                operator.write_to(&mut data);
+            }
+        }
+        page.set("/Contents", self.new_stream(data).into_indirect());
+        Ok(())
+    }
+}
+
+// Serialize the operand, strings are written in the literal form with the special characters escaped
+fn write_operand(data: &mut Vec<u8>, operand: &QPdfObject) {
+    if operand.is_string() {
+        write_literal_string(data, &operand.as_binary_string());
+    } else if operand.is_array() {
+        data.push(b'[');
+        for (i, item) in QPdfArray::from(operand.clone()).iter().enumerate() {
+            if i > 0 {
+                data.push(b' ');
+            }
+            write_operand(data, &item);
+        }
+        data.push(b']');
+    } else if operand.is_dictionary() {
+        data.extend_from_slice(b"<<");
+        write_dictionary_entries(data, &operand.clone().into());
+        data.extend_from_slice(b" >>");
+    } else {
+        // names are written with the # escapes and numbers in the fixed point form
+        data.extend_from_slice(operand.to_binary().as_bytes());
+    }
+}
+
+fn write_dictionary_entries(data: &mut Vec<u8>, dictionary: &QPdfDictionary) {
+    for key in dictionary.keys() {
+        if let Some(value) = dictionary.get(&key) {
+            data.push(b' ');
+            write_operand(data, &dictionary.owner().new_name(&key));
+            data.push(b' ');
+            write_operand(data, &value);
+        }
+    }
+}
+
+fn write_literal_string(data: &mut Vec<u8>, text: &[u8]) {
+    data.push(b'(');
+    for ch in text {
+        match ch {
+            b'(' | b')' | b'\\' => data.extend_from_slice(&[b'\\', *ch]),
+            b'\r' => data.extend_from_slice(b"\\r"),
+            b'\n' => data.extend_from_slice(b"\\n"),
+            _ => data.push(*ch),
+        }
+    }
+    data.push(b')');
+}
+
+// Join the operand tokens for the object parser, strings are converted to hex form to keep the text ASCII
+fn operand_text(tokens: &[QPdfToken]) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            if token.token_type() == QPdfTokenType::String {
+                let hex = decode_string(token.raw())
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>();
+                format!("<{}>", hex)
+            } else {
+                String::from_utf8_lossy(token.raw()).into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn damaged_content(description: String) -> QPdfError {
+    QPdfError::new(QPdfErrorCode::DamagedPdf, description)
+}
+
+impl AsRef<[u8]> for ContentBuilder {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+// Format a number for the content stream without exponent and trailing zeros
+pub(crate) fn format_number(value: f64) -> String {
+    let result = format!("{:.6}", value);
+    let result = result.trim_end_matches('0').trim_end_matches('.');
+    match result {
+        "-0" | "" => "0".to_owned(),
+        _ => result.to_owned(),
+    }
+}
@@ -3,7 +3,22 @@ use std::{
     fmt, ptr,
 };
 
-use crate::{QPdfObject, QPdfObjectLike, QPdfObjectType, QPdfStreamData, Result};
+use crate::{
+    page_boxes::rect_from_object,
+    tokenizer::{QPdfTokenType, QPdfTokenizer},
+    QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike, QPdfStreamData, Result,
+};
+
+/// Policy for the conflicting keys in `QPdfDictionary::merge`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
+pub enum ConflictPolicy {
+    /// Replace the value with the one from the merged dictionary
+    Overwrite,
+    /// Keep the existing value
+    Keep,
+    /// Fail with an error
+    Error,
+}
 
 /// QPdfDictionary wraps a QPdfObject for dictionary-related operations
 pub struct QPdfDictionary {
@@ -27,6 +42,44 @@ impl QPdfDictionary {
         }
     }
 
+    /// Return true if the page object uses shadings, either via the `/Shading` resources
+    /// or via the `sh` operator in the page contents
+    pub fn uses_shadings(&self) -> bool {
+        let has_shading_resources = self
+            .get_inherited("/Resources")
+            .filter(|resources| resources.is_dictionary())
+            .and_then(|resources| QPdfDictionary::from(resources).get("/Shading"))
+            .map(|shading| shading.is_dictionary() && !QPdfDictionary::from(shading).keys().is_empty())
+            .unwrap_or(false);
+
+        has_shading_resources
+            || self
+                .get_page_content_data()
+                .map(|data| {
+                    QPdfTokenizer::new(&data)
+                        .any(|token| token.token_type() == QPdfTokenType::Operator && token.raw() == b"sh")
+                })
+                .unwrap_or(false)
+    }
+
+    /// Get dictionary element for the specified key, following the `/Parent` chain of the page tree
+    pub(crate) fn get_inherited(&self, key: &str) -> Option<QPdfObject> {
+        let mut visited = Vec::new();
+        let mut node = self.inner.clone();
+        loop {
+            let dict = QPdfDictionary::from(node);
+            if let Some(value) = dict.get(key) {
+                return Some(value);
+            }
+            let parent = dict.get("/Parent")?;
+            if !parent.is_dictionary() || visited.contains(&parent.get_id()) {
+                return None;
+            }
+            visited.push(parent.get_id());
+            node = parent;
+        }
+    }
+
     /// Check whether there is a key in the dictionary
     pub fn has(&self, key: &str) -> bool {
         unsafe {
@@ -41,7 +94,7 @@ impl QPdfDictionary {
             let key_str = CString::new(key).unwrap();
             let oh = qpdf_sys::qpdf_oh_get_key(self.inner.owner.inner(), self.inner.inner, key_str.as_ptr());
             let obj = QPdfObject::new(self.inner.owner.clone(), oh);
-            if obj.get_type() != QPdfObjectType::Null {
+            if !obj.is_null() {
                 Some(obj)
             } else {
                 None
@@ -49,6 +102,32 @@ impl QPdfDictionary {
         }
     }
 
+    // Get all entries without resolving them, including the null values and the references to missing objects
+    pub(crate) fn raw_entries(&self) -> Vec<(String, QPdfObject)> {
+        self.keys()
+            .into_iter()
+            .map(|key| unsafe {
+                let key_str = CString::new(key.as_str()).unwrap();
+                let oh = qpdf_sys::qpdf_oh_get_key(self.inner.owner.inner(), self.inner.inner, key_str.as_ptr());
+                (key, QPdfObject::new(self.inner.owner.clone(), oh))
+            })
+            .collect()
+    }
+
+    /// Get dictionary element for the specified key or insert the result of `f` if there is none.
+    /// A key with a null value is treated as absent, as with `get`.
+    /// Note that a null value returned by `f` removes the key from the dictionary.
+    pub fn get_or_insert_with<F: FnOnce() -> QPdfObject>(&self, key: &str, f: F) -> QPdfObject {
+        match self.get(key) {
+            Some(value) => value,
+            None => {
+                let value = f();
+                self.set(key, &value);
+                value
+            }
+        }
+    }
+
     /// Set dictionary element for the specified key
     pub fn set<V: AsRef<QPdfObject>>(&self, key: &str, value: V) {
         unsafe {
@@ -62,6 +141,18 @@ impl QPdfDictionary {
         }
     }
 
+    /// Get rectangle such as `/MediaBox` for the specified key. Return `None` if the value is not an array of 4 numbers.
+    pub fn get_rect(&self, key: &str) -> Option<[f64; 4]> {
+        rect_from_object(self.get(key))
+    }
+
+    /// Set rectangle such as `/MediaBox` for the specified key. Whole values are stored as integers.
+    pub fn set_rect(&self, key: &str, rect: [f64; 4]) {
+        let owner = &self.inner.owner;
+        let array = owner.new_array_from(rect.map(|value| owner.new_number(value)));
+        self.set(key, &array);
+    }
+
     /// Remove dictionary element
     pub fn remove(&self, key: &str) {
         unsafe {
@@ -70,6 +161,39 @@ impl QPdfDictionary {
         }
     }
 
+    /// Merge another dictionary into this one. Keys which are absent here are copied, values which are
+    /// dictionaries on both sides are merged recursively and other conflicting values are resolved
+    /// according to `on_conflict`. Values which are structurally equal are not considered conflicting.
+    /// The other dictionary may belong to a different document, its values are copied into this one.
+    /// With `ConflictPolicy::Error` the keys merged before the conflict was found remain merged.
+    pub fn merge(&self, other: &QPdfDictionary, on_conflict: ConflictPolicy) -> Result<()> {
+        let owner = &self.inner.owner;
+        for key in other.keys() {
+            let other_value = match other.get(&key) {
+                Some(value) => value,
+                None => continue,
+            };
+            match self.get(&key) {
+                None => self.set(&key, owner.import_object(other_value)),
+                Some(value) if value.is_dictionary() && other_value.is_dictionary() => {
+                    QPdfDictionary::from(value).merge(&other_value.into(), on_conflict)?;
+                }
+                Some(value) if value == other_value || value.structural_eq(&other_value) => {}
+                Some(_) => match on_conflict {
+                    ConflictPolicy::Overwrite => self.set(&key, owner.import_object(other_value)),
+                    ConflictPolicy::Keep => {}
+                    ConflictPolicy::Error => {
+                        return Err(QPdfError::new(
+                            QPdfErrorCode::ObjectError,
+                            format!("Conflicting values for the dictionary key {}", key),
+                        ))
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
     /// Return all keys from the dictionary
     pub fn keys(&self) -> Vec<String> {
         let mut keys = Vec::new();
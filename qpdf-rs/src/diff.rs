@@ -0,0 +1,82 @@
+use std::collections::BTreeSet;
+
+use crate::{QPdf, QPdfDictionary, QPdfObjectLike, Result};
+
+/// Single difference between two documents found by `QPdf::diff`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PdfDifference {
+    /// The other document has an extra page at this index
+    PageAdded { index: u32 },
+    /// The page at this index is missing in the other document
+    PageRemoved { index: u32 },
+    /// The decoded page contents differ at this index
+    PageContentChanged { index: u32 },
+    /// The catalog entry was added, removed or changed
+    CatalogKeyChanged { key: String },
+    /// The document information entry was added, removed or changed
+    MetadataKeyChanged { key: String },
+}
+
+impl QPdf {
+    /// Compare this document with another one and return the list of differences.
+    ///
+    /// The pages are compared by index using their decoded contents, the catalog entries except `/Pages`
+    /// and the `/Info` entries are compared structurally. This is not a semantic diff: page resources,
+    /// annotations and the object layout are not compared, and a page inserted in the middle is reported
+    /// as changed contents of the following pages.
+    pub fn diff(&self, other: &QPdf) -> Result<Vec<PdfDifference>> {
+        let mut result = Vec::new();
+
+        let (pages, other_pages) = (self.get_pages()?, other.get_pages()?);
+        for (index, (page, other_page)) in pages.iter().zip(other_pages.iter()).enumerate() {
+            if page.get_page_content_data()?.as_ref() != other_page.get_page_content_data()?.as_ref() {
+                result.push(PdfDifference::PageContentChanged { index: index as u32 });
+            }
+        }
+        for index in other_pages.len()..pages.len() {
+            result.push(PdfDifference::PageRemoved { index: index as u32 });
+        }
+        for index in pages.len()..other_pages.len() {
+            result.push(PdfDifference::PageAdded { index: index as u32 });
+        }
+
+        for key in changed_keys(self.get_root().as_ref(), other.get_root().as_ref()) {
+            if key != "/Pages" {
+                result.push(PdfDifference::CatalogKeyChanged { key });
+            }
+        }
+
+        let info = |qpdf: &QPdf| {
+            qpdf.get_trailer()
+                .and_then(|trailer| trailer.get("/Info"))
+                .filter(|info| info.is_dictionary())
+                .map(QPdfDictionary::from)
+        };
+        for key in changed_keys(info(self).as_ref(), info(other).as_ref()) {
+            result.push(PdfDifference::MetadataKeyChanged { key });
+        }
+
+        Ok(result)
+    }
+}
+
+// Return the sorted keys which are present in one dictionary only or have structurally different values
+fn changed_keys(dict: Option<&QPdfDictionary>, other: Option<&QPdfDictionary>) -> Vec<String> {
+    let keys = dict
+        .into_iter()
+        .chain(other)
+        .flat_map(|dict| dict.keys())
+        .collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .filter(|key| {
+            let value = dict.and_then(|dict| dict.get(key));
+            let other_value = other.and_then(|other| other.get(key));
+            match (value, other_value) {
+                (Some(value), Some(other_value)) => !value.structural_eq(&other_value),
+                (None, None) => false,
+                _ => true,
+            }
+        })
+        .collect()
+}
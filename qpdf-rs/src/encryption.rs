@@ -0,0 +1,275 @@
+use std::ffi::CString;
+
+use crate::{QPdf, Result};
+
+/// Print permission for R3 and later encryption
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash, Default)]
+pub enum PrintPermission {
+    #[default]
+    Full,
+    Low,
+    None,
+}
+
+impl PrintPermission {
+    pub(crate) fn as_qpdf_enum(&self) -> qpdf_sys::qpdf_r3_print_e {
+        match self {
+            PrintPermission::Full => qpdf_sys::qpdf_r3_print_e_qpdf_r3p_full,
+            PrintPermission::Low => qpdf_sys::qpdf_r3_print_e_qpdf_r3p_low,
+            PrintPermission::None => qpdf_sys::qpdf_r3_print_e_qpdf_r3p_none,
+        }
+    }
+}
+
+/// Encryption parameters for R2 (40-bit RC4)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EncryptionParamsR2 {
+    pub user_password: String,
+    pub owner_password: String,
+    pub allow_print: bool,
+    pub allow_modify: bool,
+    pub allow_extract: bool,
+    pub allow_annotate: bool,
+}
+
+/// Encryption parameters for R3 (128-bit RC4)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EncryptionParamsR3 {
+    pub user_password: String,
+    pub owner_password: String,
+    pub allow_accessibility: bool,
+    pub allow_extract: bool,
+    pub allow_assemble: bool,
+    pub allow_annotate_and_form: bool,
+    pub allow_form_filling: bool,
+    pub allow_modify_other: bool,
+    pub allow_print: PrintPermission,
+}
+
+/// Encryption parameters for R4 (128-bit RC4 or AES)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EncryptionParamsR4 {
+    pub user_password: String,
+    pub owner_password: String,
+    pub allow_accessibility: bool,
+    pub allow_extract: bool,
+    pub allow_assemble: bool,
+    pub allow_annotate_and_form: bool,
+    pub allow_form_filling: bool,
+    pub allow_modify_other: bool,
+    pub allow_print: PrintPermission,
+    pub encrypt_metadata: bool,
+    pub use_aes: bool,
+}
+
+/// Encryption parameters for R5 (256-bit AES, deprecated Adobe extension level 3)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EncryptionParamsR5 {
+    pub user_password: String,
+    pub owner_password: String,
+    pub allow_accessibility: bool,
+    pub allow_extract: bool,
+    pub allow_assemble: bool,
+    pub allow_annotate_and_form: bool,
+    pub allow_form_filling: bool,
+    pub allow_modify_other: bool,
+    pub allow_print: PrintPermission,
+    pub encrypt_metadata: bool,
+}
+
+/// Encryption parameters for R6 (256-bit AES)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EncryptionParamsR6 {
+    pub user_password: String,
+    pub owner_password: String,
+    pub allow_accessibility: bool,
+    pub allow_extract: bool,
+    pub allow_assemble: bool,
+    pub allow_annotate_and_form: bool,
+    pub allow_form_filling: bool,
+    pub allow_modify_other: bool,
+    pub allow_print: PrintPermission,
+    pub encrypt_metadata: bool,
+}
+
+/// Permissions of the opened document, all of them are allowed for the unencrypted documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Permissions {
+    pub accessibility: bool,
+    pub extract_all: bool,
+    pub print_low_res: bool,
+    pub print_high_res: bool,
+    pub modify_assembly: bool,
+    pub modify_form: bool,
+    pub modify_annotation: bool,
+    pub modify_other: bool,
+    pub modify_all: bool,
+}
+
+impl QPdf {
+    /// Get the document permissions from the encryption dictionary
+    pub fn get_permissions(&self) -> Permissions {
+        let inner = self.inner();
+        unsafe {
+            Permissions {
+                accessibility: qpdf_sys::qpdf_allow_accessibility(inner) != 0,
+                extract_all: qpdf_sys::qpdf_allow_extract_all(inner) != 0,
+                print_low_res: qpdf_sys::qpdf_allow_print_low_res(inner) != 0,
+                print_high_res: qpdf_sys::qpdf_allow_print_high_res(inner) != 0,
+                modify_assembly: qpdf_sys::qpdf_allow_modify_assembly(inner) != 0,
+                modify_form: qpdf_sys::qpdf_allow_modify_form(inner) != 0,
+                modify_annotation: qpdf_sys::qpdf_allow_modify_annotation(inner) != 0,
+                modify_other: qpdf_sys::qpdf_allow_modify_other(inner) != 0,
+                modify_all: qpdf_sys::qpdf_allow_modify_all(inner) != 0,
+            }
+        }
+    }
+}
+
+/// Encryption parameters for the writer
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptionParams {
+    R2(EncryptionParamsR2),
+    R3(EncryptionParamsR3),
+    R4(EncryptionParamsR4),
+    R5(EncryptionParamsR5),
+    R6(EncryptionParamsR6),
+}
+
+impl EncryptionParams {
+    /// AES-256 (R6) encryption which denies all permissions, metadata is encrypted
+    pub fn aes256_all_denied(user_password: &str, owner_password: &str) -> Self {
+        EncryptionParams::R6(EncryptionParamsR6 {
+            user_password: user_password.to_owned(),
+            owner_password: owner_password.to_owned(),
+            allow_print: PrintPermission::None,
+            encrypt_metadata: true,
+            ..Default::default()
+        })
+    }
+
+    /// AES-256 (R6) encryption which allows high resolution printing only, metadata is encrypted
+    pub fn aes256_print_only(user_password: &str, owner_password: &str) -> Self {
+        EncryptionParams::R6(EncryptionParamsR6 {
+            user_password: user_password.to_owned(),
+            owner_password: owner_password.to_owned(),
+            allow_print: PrintPermission::Full,
+            encrypt_metadata: true,
+            ..Default::default()
+        })
+    }
+
+    /// 128-bit RC4 (R3) encryption with all permissions allowed, for the old readers without AES support.
+    /// RC4 is insecure and should only be used for compatibility.
+    pub fn rc4_legacy(user_password: &str, owner_password: &str) -> Self {
+        EncryptionParams::R3(EncryptionParamsR3 {
+            user_password: user_password.to_owned(),
+            owner_password: owner_password.to_owned(),
+            allow_accessibility: true,
+            allow_extract: true,
+            allow_assemble: true,
+            allow_annotate_and_form: true,
+            allow_form_filling: true,
+            allow_modify_other: true,
+            allow_print: PrintPermission::Full,
+        })
+    }
+
+    // Apply the parameters to the initialized writer, `encrypt_metadata` overrides the flag of R4 and later
+    pub(crate) fn apply(&self, owner: &QPdf, encrypt_metadata: Option<bool>) -> Result<()> {
+        let inner = owner.inner();
+        match self {
+            EncryptionParams::R2(params) => {
+                let user_password = CString::new(params.user_password.as_str())?;
+                let owner_password = CString::new(params.owner_password.as_str())?;
+                owner.wrap_ffi_call(|| unsafe {
+                    qpdf_sys::qpdf_set_r2_encryption_parameters(
+                        inner,
+                        user_password.as_ptr(),
+                        owner_password.as_ptr(),
+                        params.allow_print.into(),
+                        params.allow_modify.into(),
+                        params.allow_extract.into(),
+                        params.allow_annotate.into(),
+                    )
+                })
+            }
+            EncryptionParams::R3(params) => {
+                let user_password = CString::new(params.user_password.as_str())?;
+                let owner_password = CString::new(params.owner_password.as_str())?;
+                owner.wrap_ffi_call(|| unsafe {
+                    qpdf_sys::qpdf_set_r3_encryption_parameters2(
+                        inner,
+                        user_password.as_ptr(),
+                        owner_password.as_ptr(),
+                        params.allow_accessibility.into(),
+                        params.allow_extract.into(),
+                        params.allow_assemble.into(),
+                        params.allow_annotate_and_form.into(),
+                        params.allow_form_filling.into(),
+                        params.allow_modify_other.into(),
+                        params.allow_print.as_qpdf_enum(),
+                    )
+                })
+            }
+            EncryptionParams::R4(params) => {
+                let user_password = CString::new(params.user_password.as_str())?;
+                let owner_password = CString::new(params.owner_password.as_str())?;
+                owner.wrap_ffi_call(|| unsafe {
+                    qpdf_sys::qpdf_set_r4_encryption_parameters2(
+                        inner,
+                        user_password.as_ptr(),
+                        owner_password.as_ptr(),
+                        params.allow_accessibility.into(),
+                        params.allow_extract.into(),
+                        params.allow_assemble.into(),
+                        params.allow_annotate_and_form.into(),
+                        params.allow_form_filling.into(),
+                        params.allow_modify_other.into(),
+                        params.allow_print.as_qpdf_enum(),
+                        encrypt_metadata.unwrap_or(params.encrypt_metadata).into(),
+                        params.use_aes.into(),
+                    )
+                })
+            }
+            EncryptionParams::R5(params) => {
+                let user_password = CString::new(params.user_password.as_str())?;
+                let owner_password = CString::new(params.owner_password.as_str())?;
+                owner.wrap_ffi_call(|| unsafe {
+                    qpdf_sys::qpdf_set_r5_encryption_parameters2(
+                        inner,
+                        user_password.as_ptr(),
+                        owner_password.as_ptr(),
+                        params.allow_accessibility.into(),
+                        params.allow_extract.into(),
+                        params.allow_assemble.into(),
+                        params.allow_annotate_and_form.into(),
+                        params.allow_form_filling.into(),
+                        params.allow_modify_other.into(),
+                        params.allow_print.as_qpdf_enum(),
+                        encrypt_metadata.unwrap_or(params.encrypt_metadata).into(),
+                    )
+                })
+            }
+            EncryptionParams::R6(params) => {
+                let user_password = CString::new(params.user_password.as_str())?;
+                let owner_password = CString::new(params.owner_password.as_str())?;
+                owner.wrap_ffi_call(|| unsafe {
+                    qpdf_sys::qpdf_set_r6_encryption_parameters2(
+                        inner,
+                        user_password.as_ptr(),
+                        owner_password.as_ptr(),
+                        params.allow_accessibility.into(),
+                        params.allow_extract.into(),
+                        params.allow_assemble.into(),
+                        params.allow_annotate_and_form.into(),
+                        params.allow_form_filling.into(),
+                        params.allow_modify_other.into(),
+                        params.allow_print.as_qpdf_enum(),
+                        encrypt_metadata.unwrap_or(params.encrypt_metadata).into(),
+                    )
+                })
+            }
+        }
+    }
+}
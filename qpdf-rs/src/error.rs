@@ -1,5 +1,5 @@
 use std::ffi::NulError;
-use std::fmt;
+use std::{cmp::Ordering, error::Error, fmt, io, sync::Arc};
 
 use crate::Result;
 
@@ -18,8 +18,17 @@ pub enum QPdfErrorCode {
 }
 
 pub(crate) fn error_or_ok(error: qpdf_sys::qpdf_error_code_e) -> Result<()> {
-    let code = match error as qpdf_sys::qpdf_error_code_e {
-        qpdf_sys::qpdf_error_code_e_qpdf_e_success => return Ok(()),
+    if error as qpdf_sys::qpdf_error_code_e == qpdf_sys::qpdf_error_code_e_qpdf_e_success {
+        return Ok(());
+    }
+    Err(QPdfError {
+        error_code: error_code_from_qpdf(error),
+        ..Default::default()
+    })
+}
+
+pub(crate) fn error_code_from_qpdf(error: qpdf_sys::qpdf_error_code_e) -> QPdfErrorCode {
+    match error as qpdf_sys::qpdf_error_code_e {
         qpdf_sys::qpdf_error_code_e_qpdf_e_internal => QPdfErrorCode::InternalError,
         qpdf_sys::qpdf_error_code_e_qpdf_e_system => QPdfErrorCode::SystemError,
         qpdf_sys::qpdf_error_code_e_qpdf_e_unsupported => QPdfErrorCode::Unsupported,
@@ -28,12 +37,7 @@ pub(crate) fn error_or_ok(error: qpdf_sys::qpdf_error_code_e) -> Result<()> {
         qpdf_sys::qpdf_error_code_e_qpdf_e_pages => QPdfErrorCode::PagesError,
         qpdf_sys::qpdf_error_code_e_qpdf_e_object => QPdfErrorCode::ObjectError,
         _ => QPdfErrorCode::Unknown,
-    };
-    Err(QPdfError {
-        error_code: code,
-        description: None,
-        position: None,
-    })
+    }
 }
 
 impl Default for QPdfErrorCode {
@@ -43,11 +47,40 @@ impl Default for QPdfErrorCode {
 }
 
 /// QPdfError holds an error code and an optional extra information
-#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct QPdfError {
     pub(crate) error_code: QPdfErrorCode,
     pub(crate) description: Option<String>,
     pub(crate) position: Option<u64>,
+    pub(crate) password_required: bool,
+    pub(crate) source: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+// The source error is not comparable, errors are compared by the remaining fields
+impl PartialEq for QPdfError {
+    fn eq(&self, other: &Self) -> bool {
+        self.error_code == other.error_code
+            && self.description == other.description
+            && self.position == other.position
+            && self.password_required == other.password_required
+    }
+}
+
+impl PartialOrd for QPdfError {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (
+            self.error_code,
+            &self.description,
+            self.position,
+            self.password_required,
+        )
+            .partial_cmp(&(
+                other.error_code,
+                &other.description,
+                other.position,
+                other.password_required,
+            ))
+    }
 }
 
 impl fmt::Display for QPdfError {
@@ -61,7 +94,11 @@ impl fmt::Display for QPdfError {
     }
 }
 
-impl std::error::Error for QPdfError {}
+impl Error for QPdfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
 
 impl QPdfError {
     pub fn error_code(&self) -> QPdfErrorCode {
@@ -75,14 +112,89 @@ impl QPdfError {
     pub fn position(&self) -> Option<u64> {
         self.position
     }
+
+    /// Return true if the encrypted document could not be opened because no password or an empty one was supplied
+    pub fn is_password_required(&self) -> bool {
+        self.error_code == QPdfErrorCode::InvalidPassword && self.password_required
+    }
+
+    /// Return true if the encrypted document could not be opened with the supplied non-empty password
+    pub fn is_wrong_password(&self) -> bool {
+        self.error_code == QPdfErrorCode::InvalidPassword && !self.password_required
+    }
+
+    /// Return true if the operation may succeed when retried with different input:
+    /// another password for `InvalidPassword` or recovery mode for `DamagedPdf`.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.error_code,
+            QPdfErrorCode::InvalidPassword | QPdfErrorCode::DamagedPdf
+        )
+    }
+
+    // QPDF reports a missing and a wrong password the same way, the caller knows which one was supplied
+    pub(crate) fn with_password_required(self, password_required: bool) -> Self {
+        QPdfError {
+            password_required: self.error_code == QPdfErrorCode::InvalidPassword && password_required,
+            ..self
+        }
+    }
+
+    pub(crate) fn new<S: Into<String>>(error_code: QPdfErrorCode, description: S) -> Self {
+        QPdfError {
+            error_code,
+            description: Some(description.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// QPdfWarning holds a non-fatal problem reported by QPDF, for example damage recovered while reading
+#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+pub struct QPdfWarning {
+    pub(crate) error_code: QPdfErrorCode,
+    pub(crate) description: String,
+    pub(crate) position: u64,
+}
+
+impl fmt::Display for QPdfWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.error_code, self.description)
+    }
+}
+
+impl QPdfWarning {
+    pub fn error_code(&self) -> QPdfErrorCode {
+        self.error_code
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
 }
 
 impl From<NulError> for QPdfError {
-    fn from(_: NulError) -> Self {
+    fn from(e: NulError) -> Self {
+        QPdfError {
+            source: Some(Arc::new(e)),
+            ..QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                "Unexpected null code in the string parameter",
+            )
+        }
+    }
+}
+
+impl From<io::Error> for QPdfError {
+    fn from(e: io::Error) -> Self {
+        let description = e.to_string();
         QPdfError {
-            error_code: QPdfErrorCode::InvalidParameter,
-            description: Some("Unexpected null code in the string parameter".to_owned()),
-            position: None,
+            source: Some(Arc::new(e)),
+            ..QPdfError::new(QPdfErrorCode::SystemError, description)
         }
     }
 }
@@ -0,0 +1,45 @@
+use sha2::{Digest, Sha256};
+
+use crate::{
+    tokenizer::{tokens_to_bytes, QPdfTokenizer},
+    QPdf, QPdfObjectLike, QPdfScalar, Result,
+};
+
+impl QPdf {
+    /// Compute a SHA-256 fingerprint of the document contents.
+    ///
+    /// The fingerprint includes the number of pages and, for every page, its effective `/MediaBox`,
+    /// `/CropBox` and `/Rotate` values together with the decoded page contents normalized by tokenization,
+    /// so whitespace and comments in the content streams do not affect it.
+    /// The document metadata, trailer `/ID`, page resources, annotations and the object layout of the file
+    /// are not included: documents which differ only in those produce the same fingerprint.
+    pub fn fingerprint(&self) -> Result<[u8; 32]> {
+        let pages = self.get_pages()?;
+
+        let mut hasher = Sha256::new();
+        hasher.update((pages.len() as u64).to_le_bytes());
+
+        for page in pages {
+            let boxes = self.get_page_boxes(&page);
+            let media_box = boxes.media_box.unwrap_or_default();
+            let crop_box = boxes.crop_box.unwrap_or(media_box);
+            for value in media_box.iter().chain(crop_box.iter()) {
+                hasher.update(value.to_le_bytes());
+            }
+
+            let rotate = page
+                .get_inherited("/Rotate")
+                .filter(|rotate| rotate.is_integer())
+                .map(|rotate| QPdfScalar::from(rotate).as_i64())
+                .unwrap_or_default();
+            hasher.update(rotate.to_le_bytes());
+
+            let data = page.get_page_content_data()?;
+            let content = tokens_to_bytes(&QPdfTokenizer::new(&data).collect::<Vec<_>>());
+            hasher.update((content.len() as u64).to_le_bytes());
+            hasher.update(&content);
+        }
+
+        Ok(hasher.finalize().into())
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    text::decode_string,
+    tokenizer::{QPdfTokenType, QPdfTokenizer},
+    QPdf, QPdfArray, QPdfDictionary, QPdfObjectLike, Result,
+};
+
+/// Font referenced from the page resources
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontInfo {
+    /// Base font name including the subset prefix, for example `/ABCDEF+Helvetica`.
+    /// Type3 fonts usually have no base font name.
+    pub base_font: Option<String>,
+    /// Font subtype, for example `/Type1`, `/TrueType` or `/Type0`
+    pub subtype: Option<String>,
+    /// The font program is embedded. Type3 fonts are always embedded as they define their glyphs.
+    pub embedded: bool,
+    /// The base font name has a subset prefix of six uppercase letters followed by `+`
+    pub subset: bool,
+}
+
+impl QPdf {
+    /// Get the fonts from the `/Resources /Font` dictionaries of all pages, which may be inherited.
+    /// Indirect fonts shared between pages are reported once. For the composite (`/Type0`) fonts
+    /// the embedding is checked on the descendant font. Fonts used by form XObjects are not included.
+    pub fn get_fonts(&self) -> Result<Vec<FontInfo>> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+
+        for page in self.get_pages()? {
+            let fonts = match page_fonts(&page) {
+                Some(fonts) => fonts,
+                None => continue,
+            };
+
+            for name in fonts.keys() {
+                let font = match fonts.get(&name) {
+                    Some(font) if font.is_dictionary() => font,
+                    _ => continue,
+                };
+                if font.is_indirect() && !visited.insert((font.get_id(), font.get_generation())) {
+                    continue;
+                }
+                result.push(font_info(&font.into()));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Count the distinct character codes shown with each font on all pages, which is the information
+    /// needed to subset the embedded fonts. QPDF does not subset fonts, so this is a report only.
+    ///
+    /// The string operands of the `Tj`, `TJ`, `'` and `"` operators are collected for the font selected
+    /// with the preceding `Tf`. The codes are one byte long for the simple fonts and two bytes long for
+    /// the composite (`/Type0`) fonts, as with the `Identity-H` encoding. The fonts are reported in the order
+    /// of the first use by the base font name, or by the resource name if it is absent. Indirect fonts shared
+    /// between pages are counted once. Text in form XObjects and annotations is not included.
+    pub fn report_font_usage(&self) -> Result<Vec<(String, usize)>> {
+        let mut usage: Vec<(String, BTreeSet<u32>)> = Vec::new();
+        let mut indirect_fonts = HashMap::new();
+
+        for page in self.get_pages()? {
+            let fonts = page_fonts(&page);
+            let mut page_fonts = HashMap::new();
+            // index in the usage list and the code length of the current font
+            let mut current: Option<(usize, usize)> = None;
+            let mut operands = Vec::new();
+
+            for token in QPdfTokenizer::new(&page.get_page_content_data()?) {
+                if token.token_type() != QPdfTokenType::Operator {
+                    operands.push(token);
+                    continue;
+                }
+
+                match token.raw() {
+                    b"Tf" => {
+                        let name = operands
+                            .iter()
+                            .find(|token| token.token_type() == QPdfTokenType::Name)
+                            .map(|name| String::from_utf8_lossy(name.raw()).into_owned());
+                        current = name.and_then(|name| {
+                            if let Some(current) = page_fonts.get(&name) {
+                                return Some(*current);
+                            }
+                            let font = fonts.as_ref()?.get(&name).filter(|font| font.is_dictionary())?;
+                            let id = (font.get_id(), font.get_generation());
+                            let font = QPdfDictionary::from(font);
+                            let code_length = match font.get("/Subtype").map(|s| s.as_name()).as_deref() {
+                                Some("/Type0") => 2,
+                                _ => 1,
+                            };
+                            let index = match indirect_fonts.get(&id) {
+                                Some(index) if font.is_indirect() => *index,
+                                _ => {
+                                    let font_name = font
+                                        .get("/BaseFont")
+                                        .filter(|base_font| base_font.is_name())
+                                        .map(|base_font| base_font.as_name())
+                                        .unwrap_or_else(|| name.clone());
+                                    usage.push((font_name, BTreeSet::new()));
+                                    if font.is_indirect() {
+                                        indirect_fonts.insert(id, usage.len() - 1);
+                                    }
+                                    usage.len() - 1
+                                }
+                            };
+                            page_fonts.insert(name, (index, code_length));
+                            Some((index, code_length))
+                        });
+                    }
+                    b"Tj" | b"TJ" | b"'" | b"\"" => {
+                        if let Some((index, code_length)) = current {
+                            for operand in operands.iter().filter(|t| t.token_type() == QPdfTokenType::String) {
+                                let codes = decode_string(operand.raw())
+                                    .chunks(code_length)
+                                    .map(|code| code.iter().fold(0, |acc, byte| acc << 8 | *byte as u32))
+                                    .collect::<Vec<_>>();
+                                usage[index].1.extend(codes);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                operands.clear();
+            }
+        }
+
+        Ok(usage.into_iter().map(|(name, codes)| (name, codes.len())).collect())
+    }
+}
+
+// Get the font resources of the page, which may be inherited
+fn page_fonts(page: &QPdfDictionary) -> Option<QPdfDictionary> {
+    page.get_inherited("/Resources")
+        .filter(|resources| resources.is_dictionary())
+        .and_then(|resources| QPdfDictionary::from(resources).get("/Font"))
+        .filter(|fonts| fonts.is_dictionary())
+        .map(QPdfDictionary::from)
+}
+
+fn font_info(font: &QPdfDictionary) -> FontInfo {
+    let base_font = font
+        .get("/BaseFont")
+        .filter(|name| name.is_name())
+        .map(|name| name.as_name());
+    let subtype = font
+        .get("/Subtype")
+        .filter(|name| name.is_name())
+        .map(|name| name.as_name());
+
+    let embedded = match subtype.as_deref() {
+        Some("/Type3") => true,
+        Some("/Type0") => font
+            .get("/DescendantFonts")
+            .filter(|fonts| fonts.is_array())
+            .and_then(|fonts| QPdfArray::from(fonts).get(0))
+            .filter(|descendant| descendant.is_dictionary())
+            .map(|descendant| has_font_file(&descendant.into()))
+            .unwrap_or(false),
+        _ => has_font_file(font),
+    };
+
+    let subset = base_font
+        .as_deref()
+        .and_then(|name| name.strip_prefix('/'))
+        .map(|name| {
+            let prefix = name.as_bytes();
+            prefix.len() > 7 && prefix[6] == b'+' && prefix[..6].iter().all(u8::is_ascii_uppercase)
+        })
+        .unwrap_or(false);
+
+    FontInfo {
+        base_font,
+        subtype,
+        embedded,
+        subset,
+    }
+}
+
+fn has_font_file(font: &QPdfDictionary) -> bool {
+    font.get("/FontDescriptor")
+        .filter(|descriptor| descriptor.is_dictionary())
+        .map(|descriptor| {
+            let descriptor = QPdfDictionary::from(descriptor);
+            ["/FontFile", "/FontFile2", "/FontFile3"]
+                .iter()
+                .any(|key| descriptor.get(key).map(|file| file.is_stream()).unwrap_or(false))
+        })
+        .unwrap_or(false)
+}
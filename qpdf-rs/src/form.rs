@@ -0,0 +1,337 @@
+use std::rc::Rc;
+
+use crate::{
+    content::format_number, QPdf, QPdfArray, QPdfDictionary, QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike,
+    QPdfObjectType, QPdfStream, Result,
+};
+
+type Matrix = [f64; 6];
+
+impl QPdf {
+    /// Draw the page `stamp_page_index` of `stamp` over every page of this document, or under it if `under` is true.
+    /// The stamp page is converted to a form XObject which is scaled to fit the crop box of each page and centered.
+    /// The form keeps the stamp resources in its own `/Resources`, so only the form itself is added
+    /// to the page resources, under a name which does not collide with the existing ones.
+    /// Page rotation is not taken into account. Neither the page tree of this document nor the stamp document
+    /// is changed, a page which inherits its resources gets its own copy of them.
+    pub fn overlay(&self, stamp: &QPdf, stamp_page_index: u32, under: bool) -> Result<()> {
+        let stamp_page = stamp.get_page(stamp_page_index).ok_or_else(|| {
+            QPdfError::new(
+                QPdfErrorCode::PagesError,
+                format!("Stamp page {} does not exist", stamp_page_index),
+            )
+        })?;
+
+        let (form, bbox) = self.form_xobject_from_page(&stamp_page)?;
+        let form = form.into_indirect();
+
+        for page in self.get_pages()? {
+            let page_box = self.get_page_boxes(&page).effective_crop_box().unwrap_or(bbox);
+            self.draw_form(&page, &form, fit_matrix(bbox, page_box), under)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new document for N-up printing where every page is a grid of `cols` by `rows` cells
+    /// filled with the consecutive pages of this document, left to right and top to bottom.
+    /// The output pages have the size of the first page, each source page is scaled to fit its cell and centered.
+    /// Rotated pages are drawn upright and their sizes are taken after the `/Rotate` is applied.
+    /// The last output page may have empty cells. This document is not changed.
+    pub fn nup(&self, cols: u32, rows: u32) -> Result<QPdf> {
+        if cols == 0 || rows == 0 {
+            return Err(QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                format!("Invalid N-up grid {}x{}", cols, rows),
+            ));
+        }
+
+        let pages = self.get_pages()?;
+        let result = QPdf::empty();
+        let forms = pages
+            .iter()
+            .map(|page| result.upright_form_xobject_from_page(page))
+            .collect::<Result<Vec<_>>>()?;
+
+        let sheet_box = forms.first().map(|(_, bbox)| *bbox).unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let (_, _, width, height) = normalize_rect(sheet_box);
+        let (cell_width, cell_height) = (width / cols as f64, height / rows as f64);
+
+        for chunk in forms.chunks((cols * rows) as usize) {
+            let sheet = result.add_blank_page(width, height, false)?;
+            for (index, (form, bbox)) in chunk.iter().enumerate() {
+                let (col, row) = ((index as u32 % cols) as f64, (index as u32 / cols) as f64);
+                let cell = [
+                    col * cell_width,
+                    height - (row + 1.0) * cell_height,
+                    (col + 1.0) * cell_width,
+                    height - row * cell_height,
+                ];
+                let form = form.as_object().clone().into_indirect();
+                result.draw_form(&sheet, &form, fit_matrix(*bbox, cell), false)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Convert the page of this or another document to a form XObject which can be drawn on other pages.
+    /// The form has the page contents, the page crop box as `/BBox`, the page `/Resources` and `/Group`,
+    /// and a `/Matrix` which compensates the page `/Rotate`, so the form is drawn upright with its lower left
+    /// corner at the origin for rotated pages. `/UserUnit` is not taken into account.
+    /// The inherited page attributes are read from the page tree, the document of the page is not changed.
+    /// The returned stream is a direct object, it should be made indirect before it is used.
+    pub fn page_as_form_xobject(&self, page: &QPdfDictionary) -> Result<QPdfStream> {
+        Ok(self.upright_form_xobject_from_page(page)?.0)
+    }
+
+    // Create a form XObject with the /Matrix which compensates the page /Rotate,
+    // return it together with its bounding box after the /Matrix is applied
+    fn upright_form_xobject_from_page(&self, page: &QPdfDictionary) -> Result<(QPdfStream, [f64; 4])> {
+        let (form, bbox) = self.form_xobject_from_page(page)?;
+        let (llx, lly, urx, ury) = (bbox[0], bbox[1], bbox[2], bbox[3]);
+        let (width, height) = (urx - llx, ury - lly);
+        let rotate = page
+            .get_inherited("/Rotate")
+            .and_then(|rotate| rotate.as_number())
+            .unwrap_or_default() as i64;
+        let (matrix, upright_bbox) = match rotate.rem_euclid(360) {
+            90 => ([0.0, -1.0, 1.0, 0.0, -lly, urx], [0.0, 0.0, height, width]),
+            180 => ([-1.0, 0.0, 0.0, -1.0, urx, ury], [0.0, 0.0, width, height]),
+            270 => ([0.0, 1.0, -1.0, 0.0, ury, -llx], [0.0, 0.0, height, width]),
+            _ => ([1.0, 0.0, 0.0, 1.0, 0.0, 0.0], bbox),
+        };
+        let matrix = self.new_array_from(matrix.map(|value| self.new_number(value)));
+        form.get_dictionary().set("/Matrix", &matrix);
+        Ok((form, upright_bbox))
+    }
+
+    // Create a form XObject from the page of this or another document, return it together with its bounding box
+    pub(crate) fn form_xobject_from_page(&self, page: &QPdfDictionary) -> Result<(QPdfStream, [f64; 4])> {
+        let bbox = self
+            .get_page_boxes(page)
+            .effective_crop_box()
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let data = page.get_page_content_data()?;
+
+        let form = self.new_stream_with_dictionary(
+            [
+                ("/Type", self.new_name("/XObject")),
+                ("/Subtype", self.new_name("/Form")),
+            ],
+            &data,
+        );
+        let dict = form.get_dictionary();
+        dict.set_rect("/BBox", bbox);
+        if let Some(resources) = page.get_inherited("/Resources") {
+            dict.set("/Resources", self.import_object(resources));
+        }
+        if let Some(group) = page.get("/Group") {
+            dict.set("/Group", self.import_object(group));
+        }
+
+        Ok((form, bbox))
+    }
+
+    // Draw the form XObject on the page with the given transformation matrix
+    pub(crate) fn draw_form(
+        &self,
+        page: &QPdfDictionary,
+        form: &QPdfObject,
+        matrix: Matrix,
+        under: bool,
+    ) -> Result<()> {
+        let name = self.add_form_resource(page, form);
+        let matrix = matrix.iter().map(|v| format_number(*v)).collect::<Vec<_>>().join(" ");
+        self.append_page_content(page, format!("q\n{} cm\n{} Do\nQ\n", matrix, name), under)
+    }
+
+    /// Draw the XObject on the page with the given transformation matrix. The XObject is added to the page
+    /// `/Resources /XObject` under `name_hint`, or under `name_hint` followed by a number if the name is taken
+    /// by another object. If the page inherits its resources, it gets its own copy of them, so the other pages
+    /// of the tree are not changed. The XObject may belong to another document, in this case it is copied.
+    /// The drawing is appended to the page content, which is wrapped in `q`/`Q` to isolate its graphics state.
+    pub fn draw_xobject(
+        &self,
+        page: &QPdfDictionary,
+        xobject: &QPdfStream,
+        matrix: [f64; 6],
+        name_hint: &str,
+    ) -> Result<()> {
+        let hint = if name_hint.starts_with('/') {
+            name_hint.to_owned()
+        } else {
+            format!("/{}", name_hint)
+        };
+        let xobject = self.import_object(xobject.as_object().clone());
+        let xobject = if xobject.is_indirect() {
+            xobject
+        } else {
+            xobject.into_indirect()
+        };
+
+        let candidates = std::iter::once(hint.clone()).chain((1..).map(|i| format!("{}{}", hint, i)));
+        let name = self.add_xobject_resource(page, &xobject, candidates);
+        let matrix = matrix.iter().map(|v| format_number(*v)).collect::<Vec<_>>().join(" ");
+        self.append_page_content(page, format!("q\n{} cm\n{} Do\nQ\n", matrix, name), false)
+    }
+
+    // Add the form XObject to the page resources and return its resource name
+    pub(crate) fn add_form_resource(&self, page: &QPdfDictionary, form: &QPdfObject) -> String {
+        self.add_xobject_resource(page, form, (0..).map(|i| format!("/Fx{}", i)))
+    }
+
+    // Add the XObject to the page resources under the first free name and return the name
+    fn add_xobject_resource<I>(&self, page: &QPdfDictionary, xobject: &QPdfObject, candidates: I) -> String
+    where
+        I: Iterator<Item = String>,
+    {
+        let resources = match page.get("/Resources").filter(|resources| resources.is_dictionary()) {
+            Some(resources) => QPdfDictionary::from(resources),
+            None => {
+                // the page gets its own copy of the inherited resources, so the other pages are not changed
+                let resources = page
+                    .get_inherited("/Resources")
+                    .filter(|resources| resources.is_dictionary())
+                    .map(|resources| self.shallow_copy_dictionary(&resources.into()))
+                    .unwrap_or_else(|| self.new_dictionary());
+                if let Some(xobjects) = resources.get("/XObject").filter(|xobjects| xobjects.is_dictionary()) {
+                    resources.set("/XObject", self.shallow_copy_dictionary(&xobjects.into()));
+                }
+                page.set("/Resources", &resources);
+                resources
+            }
+        };
+        let xobjects: QPdfDictionary = resources
+            .get_or_insert_with("/XObject", || self.new_dictionary().into())
+            .into();
+        let name = xobject_name(&xobjects, xobject, candidates);
+        xobjects.set(&name, xobject);
+        name
+    }
+
+    fn shallow_copy_dictionary(&self, dict: &QPdfDictionary) -> QPdfDictionary {
+        let copy = self.new_dictionary();
+        for (key, value) in dict.raw_entries() {
+            copy.set(&key, &value);
+        }
+        copy
+    }
+
+    // Add the content before or after the existing page content, which is wrapped in q/Q
+    // so that its graphics state does not leak into the added content
+    pub(crate) fn append_page_content<D: AsRef<[u8]>>(
+        &self,
+        page: &QPdfDictionary,
+        data: D,
+        under: bool,
+    ) -> Result<()> {
+        let stamp = self.new_stream(data).into_indirect();
+
+        let contents = self.new_array();
+        if under {
+            contents.push(&stamp);
+        } else {
+            contents.push(self.new_stream("q\n").into_indirect());
+        }
+        match page.get("/Contents") {
+            Some(existing) if existing.is_array() => {
+                for item in QPdfArray::from(existing).iter() {
+                    contents.push(&item);
+                }
+            }
+            Some(existing) if existing.is_stream() => contents.push(&existing),
+            _ => {}
+        }
+        if !under {
+            contents.push(self.new_stream("\nQ\n").into_indirect());
+            contents.push(&stamp);
+        }
+        page.set("/Contents", &contents);
+
+        Ok(())
+    }
+
+    // Copy inheritable page attributes from the page tree nodes to the pages
+    pub(crate) fn push_inherited_attributes(&self) {
+        unsafe {
+            qpdf_sys::qpdf_push_inherited_attributes_to_page(self.inner());
+        }
+    }
+
+    // Return the object itself if it belongs to this document or a copy of the foreign object.
+    // The foreign document is not modified.
+    pub(crate) fn import_object(&self, obj: QPdfObject) -> QPdfObject {
+        if Rc::ptr_eq(&obj.owner.inner, &self.inner) {
+            obj
+        } else if obj.is_indirect() {
+            self.copy_from_foreign(obj)
+        } else {
+            self.copy_direct_from_foreign(&obj)
+        }
+    }
+
+    // QPDF copies only the indirect foreign objects, the direct containers are rebuilt here
+    fn copy_direct_from_foreign(&self, obj: &QPdfObject) -> QPdfObject {
+        match obj.get_type() {
+            QPdfObjectType::Dictionary => {
+                let dict = self.new_dictionary();
+                for (key, value) in QPdfDictionary::from(obj.clone()).raw_entries() {
+                    dict.set(&key, self.import_object(value));
+                }
+                dict.into()
+            }
+            QPdfObjectType::Array => {
+                let array = QPdfArray::from(obj.clone());
+                self.new_array_from(array.iter().map(|item| self.import_object(item)))
+                    .into()
+            }
+            QPdfObjectType::String => self.new_binary_string(obj.as_binary_string()),
+            QPdfObjectType::Name => self.new_name(&obj.as_name()),
+            _ => self.parse_object(&obj.to_string()).unwrap_or_else(|_| self.new_null()),
+        }
+    }
+}
+
+// Return the name of the XObject in the resources, reusing the existing entry for the same object,
+// otherwise the first candidate which is not taken
+fn xobject_name<I>(xobjects: &QPdfDictionary, xobject: &QPdfObject, mut candidates: I) -> String
+where
+    I: Iterator<Item = String>,
+{
+    let keys = xobjects.keys();
+    let existing = keys.iter().find(|key| {
+        xobjects
+            .get(key)
+            .map(|value| {
+                value.is_indirect()
+                    && (value.get_id(), value.get_generation()) == (xobject.get_id(), xobject.get_generation())
+            })
+            .unwrap_or(false)
+    });
+    match existing {
+        Some(key) => key.clone(),
+        None => candidates.find(|name| !keys.contains(name)).unwrap_or_default(),
+    }
+}
+
+// Compute the matrix which scales the source rectangle to fit the destination one and centers it
+fn fit_matrix(src: [f64; 4], dst: [f64; 4]) -> Matrix {
+    let (sx, sy, sw, sh) = normalize_rect(src);
+    let (dx, dy, dw, dh) = normalize_rect(dst);
+    let scale = if sw > 0.0 && sh > 0.0 {
+        (dw / sw).min(dh / sh)
+    } else {
+        1.0
+    };
+    let tx = dx + (dw - sw * scale) / 2.0 - sx * scale;
+    let ty = dy + (dh - sh * scale) / 2.0 - sy * scale;
+    [scale, 0.0, 0.0, scale, tx, ty]
+}
+
+// Return lower left corner, width and height of the rectangle
+fn normalize_rect(rect: [f64; 4]) -> (f64, f64, f64, f64) {
+    let (llx, urx) = (rect[0].min(rect[2]), rect[0].max(rect[2]));
+    let (lly, ury) = (rect[1].min(rect[3]), rect[1].max(rect[3]));
+    (llx, lly, urx - llx, ury - lly)
+}
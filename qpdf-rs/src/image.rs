@@ -0,0 +1,199 @@
+use crate::{
+    QPdf, QPdfArray, QPdfDictionary, QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike, QPdfScalar, QPdfStream,
+    QPdfStreamData, Result, StreamDecodeLevel,
+};
+
+/// Image XObject referenced from the page resources
+pub struct QPdfImage {
+    name: String,
+    stream: QPdfStream,
+}
+
+impl QPdfImage {
+    /// Get the resource name of the image, for example `/Im1`. For the page thumbnails this is `/Thumb`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the underlying image stream
+    pub fn stream(&self) -> &QPdfStream {
+        &self.stream
+    }
+
+    /// Get the image width in pixels
+    pub fn width(&self) -> Option<u32> {
+        self.get_integer("/Width")
+    }
+
+    /// Get the image height in pixels
+    pub fn height(&self) -> Option<u32> {
+        self.get_integer("/Height")
+    }
+
+    /// Get the number of bits per color component. Image masks and JPX images may omit it.
+    pub fn bits_per_component(&self) -> Option<u32> {
+        self.get_integer("/BitsPerComponent")
+    }
+
+    /// Get the color space name, for example `/DeviceRGB`. For the color space arrays such as
+    /// `[/ICCBased 5 0 R]` or `[/Indexed ...]` the family name is returned.
+    pub fn color_space(&self) -> Option<String> {
+        let color_space = self.stream.get_dictionary().get("/ColorSpace")?;
+        if color_space.is_name() {
+            Some(color_space.as_name())
+        } else if color_space.is_array() {
+            QPdfArray::from(color_space)
+                .get(0)
+                .filter(|family| family.is_name())
+                .map(|family| family.as_name())
+        } else {
+            None
+        }
+    }
+
+    /// Get the image data. The data is decoded into pixel bytes except for the lossy compressed images
+    /// (`/DCTDecode`, `/JPXDecode`) and the bilevel codecs (`/CCITTFaxDecode`, `/JBIG2Decode`),
+    /// for which the encoded image file data is returned.
+    pub fn get_data(&self) -> Result<QPdfStreamData> {
+        self.stream.get_data(StreamDecodeLevel::Specialized)
+    }
+
+    fn get_integer(&self, key: &str) -> Option<u32> {
+        self.stream
+            .get_dictionary()
+            .get(key)
+            .filter(|value| value.is_integer())
+            .map(|value| QPdfScalar::from(value).as_u32())
+    }
+}
+
+/// Inline image found between the `BI` and `EI` operators of the page contents
+pub struct InlineImage {
+    dictionary: QPdfDictionary,
+    data: Vec<u8>,
+}
+
+impl InlineImage {
+    /// Get the image dictionary as it appears in the content stream, the keys may be abbreviated
+    pub fn dictionary(&self) -> &QPdfDictionary {
+        &self.dictionary
+    }
+
+    /// Get the image data as it appears in the content stream, with the filters still applied
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the image width in pixels
+    pub fn width(&self) -> Option<u32> {
+        self.get_integer("/W", "/Width")
+    }
+
+    /// Get the image height in pixels
+    pub fn height(&self) -> Option<u32> {
+        self.get_integer("/H", "/Height")
+    }
+
+    /// Get the number of bits per color component
+    pub fn bits_per_component(&self) -> Option<u32> {
+        self.get_integer("/BPC", "/BitsPerComponent")
+    }
+
+    /// Get the color space name with the abbreviations expanded, for example `/DeviceRGB` for `/RGB`.
+    /// For the color space arrays the family name is returned, other names refer to the page
+    /// `/ColorSpace` resources and are returned as is.
+    pub fn color_space(&self) -> Option<String> {
+        let color_space = self.get("/CS", "/ColorSpace")?;
+        let name = if color_space.is_array() {
+            QPdfArray::from(color_space)
+                .get(0)
+                .filter(|family| family.is_name())?
+                .as_name()
+        } else if color_space.is_name() {
+            color_space.as_name()
+        } else {
+            return None;
+        };
+        let name = match name.as_str() {
+            "/G" => "/DeviceGray",
+            "/RGB" => "/DeviceRGB",
+            "/CMYK" => "/DeviceCMYK",
+            "/I" => "/Indexed",
+            other => other,
+        };
+        Some(name.to_owned())
+    }
+
+    fn get(&self, short_key: &str, key: &str) -> Option<QPdfObject> {
+        self.dictionary.get(short_key).or_else(|| self.dictionary.get(key))
+    }
+
+    fn get_integer(&self, short_key: &str, key: &str) -> Option<u32> {
+        self.get(short_key, key)
+            .filter(|value| value.is_integer())
+            .map(|value| QPdfScalar::from(value).as_u32())
+    }
+}
+
+impl QPdf {
+    /// Get the image XObjects from the page `/Resources /XObject` dictionary, which may be inherited
+    /// from the page tree. Inline images and the images nested in form XObjects are not included.
+    pub fn get_page_images(&self, page: &QPdfDictionary) -> Result<Vec<QPdfImage>> {
+        let xobjects = page
+            .get_inherited("/Resources")
+            .filter(|resources| resources.is_dictionary())
+            .and_then(|resources| QPdfDictionary::from(resources).get("/XObject"))
+            .filter(|xobjects| xobjects.is_dictionary())
+            .map(QPdfDictionary::from);
+
+        let xobjects = match xobjects {
+            Some(xobjects) => xobjects,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(xobjects
+            .keys()
+            .into_iter()
+            .filter_map(|name| {
+                let stream = xobjects.get(&name).filter(|xobject| xobject.is_stream())?;
+                let stream = QPdfStream::from(stream);
+                let subtype = stream.get_dictionary().get("/Subtype").map(|s| s.as_name());
+                (subtype.as_deref() == Some("/Image")).then_some(QPdfImage { name, stream })
+            })
+            .collect())
+    }
+
+    /// Get the page thumbnail image from the `/Thumb` entry, `None` if the page has no thumbnail
+    pub fn get_page_thumbnail(&self, page: &QPdfDictionary) -> Option<QPdfImage> {
+        let stream = page.get("/Thumb").filter(|thumb| thumb.is_stream())?;
+        Some(QPdfImage {
+            name: "/Thumb".to_owned(),
+            stream: stream.into(),
+        })
+    }
+
+    /// Get the inline images from the page contents. Inline images in form XObjects are not included.
+    /// The contents is parsed with `parse_content`, so a truncated `BI` block or a malformed token
+    /// results in `QPdfErrorCode::DamagedPdf`.
+    pub fn get_inline_images(&self, page: &QPdfDictionary) -> Result<Vec<InlineImage>> {
+        let operators = self.parse_content(&page.get_page_content_data()?)?;
+        let mut result = Vec::new();
+
+        for operator in operators.into_iter().filter(|op| op.operator == "BI") {
+            match operator.operands.as_slice() {
+                [dictionary, data] if dictionary.is_dictionary() => result.push(InlineImage {
+                    dictionary: dictionary.clone().into(),
+                    data: data.as_binary_string(),
+                }),
+                _ => {
+                    return Err(QPdfError::new(
+                        QPdfErrorCode::DamagedPdf,
+                        "Invalid inline image dictionary",
+                    ))
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfObjectLike, QPdfStream, Result, StreamDecodeLevel};
+
+/// Output intent describing the color characteristics of the intended output device
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OutputIntent {
+    /// Output intent subtype, for example `/GTS_PDFX` or `/GTS_PDFA1`
+    pub subtype: String,
+    /// Identifier of the intended output condition, for example `FOGRA39`
+    pub output_condition_identifier: String,
+    /// Decoded ICC profile data
+    pub dest_output_profile: Option<Vec<u8>>,
+}
+
+impl QPdf {
+    /// Get output intents from the `/OutputIntents` array of the document catalog
+    pub fn output_intents(&self) -> Result<Vec<OutputIntent>> {
+        let intents: QPdfArray = match self.get_root_or_error()?.get("/OutputIntents") {
+            Some(intents) if intents.is_array() => intents.into(),
+            _ => return Ok(Vec::new()),
+        };
+
+        intents
+            .iter()
+            .filter(|intent| intent.is_dictionary())
+            .map(|intent| {
+                let intent = QPdfDictionary::from(intent);
+                let dest_output_profile = match intent.get("/DestOutputProfile") {
+                    Some(profile) if profile.is_stream() => Some(
+                        QPdfStream::from(profile)
+                            .get_data(StreamDecodeLevel::Generalized)?
+                            .into_vec(),
+                    ),
+                    _ => None,
+                };
+                Ok(OutputIntent {
+                    subtype: intent.get("/S").map(|s| s.as_name()).unwrap_or_default(),
+                    output_condition_identifier: intent
+                        .get("/OutputConditionIdentifier")
+                        .map(|s| s.as_string())
+                        .unwrap_or_default(),
+                    dest_output_profile,
+                })
+            })
+            .collect()
+    }
+
+    /// Add output intent to the `/OutputIntents` array of the document catalog, creating the array if needed
+    pub fn add_output_intent(&self, intent: OutputIntent) -> Result<()> {
+        let root = self.get_root_or_error()?;
+
+        let dict = self.new_dictionary_from([
+            ("/Type", self.new_name("/OutputIntent")),
+            ("/S", self.new_name(&intent.subtype)),
+            (
+                "/OutputConditionIdentifier",
+                self.new_string(&intent.output_condition_identifier),
+            ),
+        ]);
+
+        if let Some(profile) = intent.dest_output_profile {
+            let stream = self.new_stream(&profile);
+            if let Some(components) = icc_components(&profile) {
+                stream.get_dictionary().set("/N", self.new_integer(components));
+            }
+            dict.set("/DestOutputProfile", &stream);
+        }
+
+        let intents: QPdfArray = match root.get("/OutputIntents") {
+            Some(intents) if intents.is_array() => intents.into(),
+            _ => {
+                let intents = self.new_array();
+                root.set("/OutputIntents", &intents);
+                intents
+            }
+        };
+        intents.push(dict.into_indirect());
+
+        Ok(())
+    }
+}
+
+// Number of color components as declared by the color space signature of the ICC profile header
+fn icc_components(profile: &[u8]) -> Option<i64> {
+    match profile.get(16..20)? {
+        b"GRAY" => Some(1),
+        b"RGB " | b"Lab " | b"XYZ " => Some(3),
+        b"CMYK" => Some(4),
+        _ => None,
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfObject, QPdfObjectLike, QPdfStream, Result, StreamDecodeLevel};
+
+impl QPdf {
+    /// Collect the source of the document level JavaScript actions from the `/Names /JavaScript` name tree
+    /// and the catalog `/OpenAction`, including the actions chained with `/Next`.
+    /// The scripts are returned in the name tree order followed by the open action ones.
+    /// The page, annotation and form field `/AA` actions are not included.
+    pub fn get_document_javascript(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+
+        if let Some(tree) = self.get_name_tree("/JavaScript") {
+            for (_, action) in tree.iter() {
+                collect_javascript(&action, &mut visited, &mut result);
+            }
+        }
+
+        if let Some(action) = self.get_root().and_then(|root| root.get("/OpenAction")) {
+            collect_javascript(&action, &mut visited, &mut result);
+        }
+
+        result
+    }
+
+    /// Remove the JavaScript actions from the document: the `/Names /JavaScript` name tree,
+    /// the JavaScript `/OpenAction`, and the JavaScript entries of the `/AA` additional actions
+    /// of the catalog, pages, annotations and form fields. The `/A` actions of annotations and fields
+    /// are sanitized too. Other actions such as `/GoTo` are kept, JavaScript actions chained
+    /// to them with `/Next` are removed from the chain.
+    pub fn remove_javascript(&self) -> Result<()> {
+        let root = self.get_root_or_error()?;
+        let mut visited = HashSet::new();
+
+        if let Some(names) = root.get("/Names").filter(|names| names.is_dictionary()) {
+            QPdfDictionary::from(names).remove("/JavaScript");
+        }
+        strip_action_entry(&root, "/OpenAction", &mut visited);
+        strip_additional_actions(&root, &mut visited);
+
+        for page in self.get_pages()? {
+            strip_additional_actions(&page, &mut visited);
+            if let Some(annots) = page.get("/Annots").filter(|annots| annots.is_array()) {
+                for annot in QPdfArray::from(annots).iter().filter(|annot| annot.is_dictionary()) {
+                    strip_field(&annot.into(), &mut visited);
+                }
+            }
+        }
+
+        if let Some(fields) = root
+            .get("/AcroForm")
+            .filter(|acro_form| acro_form.is_dictionary())
+            .and_then(|acro_form| QPdfDictionary::from(acro_form).get("/Fields"))
+            .filter(|fields| fields.is_array())
+        {
+            for field in QPdfArray::from(fields).iter().filter(|field| field.is_dictionary()) {
+                strip_field(&field.into(), &mut visited);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Sanitize the annotation or form field and its /Kids
+fn strip_field(field: &QPdfDictionary, visited: &mut HashSet<(u32, u32)>) {
+    if field.is_indirect() && !visited.insert((field.get_id(), field.get_generation())) {
+        return;
+    }
+    strip_action_entry(field, "/A", visited);
+    strip_additional_actions(field, visited);
+    if let Some(kids) = field.get("/Kids").filter(|kids| kids.is_array()) {
+        for kid in QPdfArray::from(kids).iter().filter(|kid| kid.is_dictionary()) {
+            strip_field(&kid.into(), visited);
+        }
+    }
+}
+
+// Remove the JavaScript triggers of the /AA dictionary, and the dictionary itself if nothing is left
+fn strip_additional_actions(dict: &QPdfDictionary, visited: &mut HashSet<(u32, u32)>) {
+    let actions = match dict.get("/AA") {
+        Some(actions) if actions.is_dictionary() => QPdfDictionary::from(actions),
+        _ => return,
+    };
+    for trigger in actions.keys() {
+        strip_action_entry(&actions, &trigger, visited);
+    }
+    if actions.keys().is_empty() {
+        dict.remove("/AA");
+    }
+}
+
+// Remove the action stored under the key if it is a JavaScript action, otherwise strip its /Next chain
+fn strip_action_entry(dict: &QPdfDictionary, key: &str, visited: &mut HashSet<(u32, u32)>) {
+    if let Some(action) = dict.get(key) {
+        if !strip_action(&action, visited) {
+            dict.remove(key);
+        }
+    }
+}
+
+// Return false if the action must be removed. Arrays are the /Next chains of several actions.
+fn strip_action(action: &QPdfObject, visited: &mut HashSet<(u32, u32)>) -> bool {
+    if action.is_array() {
+        let mut array = QPdfArray::from(action.clone());
+        let mut index = 0;
+        while let Some(item) = array.get(index) {
+            if strip_action(&item, visited) {
+                index += 1;
+            } else {
+                array.remove(index);
+            }
+        }
+        return true;
+    }
+    if !action.is_dictionary() {
+        return true;
+    }
+
+    let action = QPdfDictionary::from(action.clone());
+    if action.get("/S").map(|s| s.as_name()).as_deref() == Some("/JavaScript") {
+        return false;
+    }
+    if !action.is_indirect() || visited.insert((action.get_id(), action.get_generation())) {
+        strip_action_entry(&action, "/Next", visited);
+    }
+    true
+}
+
+// Collect the scripts of the action and of the actions from its /Next entry, which may be a single
+// action or an array of them
+fn collect_javascript(action: &QPdfObject, visited: &mut HashSet<(u32, u32)>, result: &mut Vec<String>) {
+    if action.is_array() {
+        for next in QPdfArray::from(action.clone()).iter() {
+            collect_javascript(&next, visited, result);
+        }
+        return;
+    }
+    if !action.is_dictionary() || (action.is_indirect() && !visited.insert((action.get_id(), action.get_generation())))
+    {
+        return;
+    }
+
+    let action = QPdfDictionary::from(action.clone());
+    if action.get("/S").map(|s| s.as_name()).as_deref() == Some("/JavaScript") {
+        if let Some(script) = action.get("/JS").and_then(|js| script_source(&js)) {
+            result.push(script);
+        }
+    }
+    if let Some(next) = action.get("/Next") {
+        collect_javascript(&next, visited, result);
+    }
+}
+
+// The script is either a text string or a stream
+fn script_source(js: &QPdfObject) -> Option<String> {
+    if js.is_string() {
+        Some(js.as_string())
+    } else if js.is_stream() {
+        let data = QPdfStream::from(js.clone())
+            .get_data(StreamDecodeLevel::Generalized)
+            .ok()?;
+        Some(String::from_utf8_lossy(&data).into_owned())
+    } else {
+        None
+    }
+}
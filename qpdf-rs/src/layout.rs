@@ -0,0 +1,109 @@
+use crate::{QPdf, QPdfObjectLike, Result};
+
+/// Page layout used by the viewer when the document is opened (`/PageLayout`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageLayout {
+    SinglePage,
+    OneColumn,
+    TwoColumnLeft,
+    TwoColumnRight,
+    TwoPageLeft,
+    TwoPageRight,
+}
+
+impl PageLayout {
+    /// Return the catalog name of the layout
+    pub fn as_name(&self) -> &'static str {
+        match self {
+            PageLayout::SinglePage => "/SinglePage",
+            PageLayout::OneColumn => "/OneColumn",
+            PageLayout::TwoColumnLeft => "/TwoColumnLeft",
+            PageLayout::TwoColumnRight => "/TwoColumnRight",
+            PageLayout::TwoPageLeft => "/TwoPageLeft",
+            PageLayout::TwoPageRight => "/TwoPageRight",
+        }
+    }
+
+    /// Return the layout for the specified name
+    pub fn from_name(name: &str) -> Option<PageLayout> {
+        match name {
+            "/SinglePage" => Some(PageLayout::SinglePage),
+            "/OneColumn" => Some(PageLayout::OneColumn),
+            "/TwoColumnLeft" => Some(PageLayout::TwoColumnLeft),
+            "/TwoColumnRight" => Some(PageLayout::TwoColumnRight),
+            "/TwoPageLeft" => Some(PageLayout::TwoPageLeft),
+            "/TwoPageRight" => Some(PageLayout::TwoPageRight),
+            _ => None,
+        }
+    }
+}
+
+/// Panel shown by the viewer when the document is opened (`/PageMode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageMode {
+    UseNone,
+    UseOutlines,
+    UseThumbs,
+    FullScreen,
+    UseOC,
+    UseAttachments,
+}
+
+impl PageMode {
+    /// Return the catalog name of the mode
+    pub fn as_name(&self) -> &'static str {
+        match self {
+            PageMode::UseNone => "/UseNone",
+            PageMode::UseOutlines => "/UseOutlines",
+            PageMode::UseThumbs => "/UseThumbs",
+            PageMode::FullScreen => "/FullScreen",
+            PageMode::UseOC => "/UseOC",
+            PageMode::UseAttachments => "/UseAttachments",
+        }
+    }
+
+    /// Return the mode for the specified name
+    pub fn from_name(name: &str) -> Option<PageMode> {
+        match name {
+            "/UseNone" => Some(PageMode::UseNone),
+            "/UseOutlines" => Some(PageMode::UseOutlines),
+            "/UseThumbs" => Some(PageMode::UseThumbs),
+            "/FullScreen" => Some(PageMode::FullScreen),
+            "/UseOC" => Some(PageMode::UseOC),
+            "/UseAttachments" => Some(PageMode::UseAttachments),
+            _ => None,
+        }
+    }
+}
+
+impl QPdf {
+    /// Set the catalog `/PageLayout`
+    pub fn set_page_layout(&self, layout: PageLayout) -> Result<()> {
+        self.get_root_or_error()?
+            .set("/PageLayout", self.new_name(layout.as_name()));
+        Ok(())
+    }
+
+    /// Get the catalog `/PageLayout`, `None` if it is absent or unknown
+    pub fn get_page_layout(&self) -> Option<PageLayout> {
+        self.get_root()?
+            .get("/PageLayout")
+            .filter(|layout| layout.is_name())
+            .and_then(|layout| PageLayout::from_name(&layout.as_name()))
+    }
+
+    /// Set the catalog `/PageMode`
+    pub fn set_page_mode(&self, mode: PageMode) -> Result<()> {
+        self.get_root_or_error()?
+            .set("/PageMode", self.new_name(mode.as_name()));
+        Ok(())
+    }
+
+    /// Get the catalog `/PageMode`, `None` if it is absent or unknown
+    pub fn get_page_mode(&self) -> Option<PageMode> {
+        self.get_root()?
+            .get("/PageMode")
+            .filter(|mode| mode.is_name())
+            .and_then(|mode| PageMode::from_name(&mode.as_name()))
+    }
+}
@@ -1,6 +1,8 @@
 #![doc = include_str!("../README.md")]
 
 use std::{
+    cell::RefCell,
+    collections::HashSet,
     ffi::{CStr, CString},
     fmt,
     path::Path,
@@ -9,29 +11,80 @@ use std::{
 };
 
 pub use array::*;
+pub use content::*;
 pub use dict::*;
+pub use diff::*;
+pub use encryption::*;
 pub use error::*;
+pub use font::*;
+pub use image::*;
+pub use intent::*;
+pub use layout::*;
+pub use name::*;
 pub use object::*;
+pub use object_stream::*;
+pub use outline::*;
+pub use page_boxes::*;
+pub use read_options::*;
 pub use scalar::*;
 pub use stream::*;
+pub use structure::*;
+pub use tokenizer::*;
+pub use tree::*;
+pub use version::*;
 pub use writer::*;
 
+mod annotations;
 pub mod array;
+pub mod content;
 pub mod dict;
+pub mod diff;
+pub mod encryption;
 pub mod error;
+mod fingerprint;
+pub mod font;
+mod form;
+pub mod image;
+pub mod intent;
+mod javascript;
+pub mod layout;
+pub mod name;
 pub mod object;
+pub mod object_stream;
+mod optional_content;
+pub mod outline;
+pub mod page_boxes;
+pub mod read_options;
+mod resources;
 pub mod scalar;
 pub mod stream;
+pub mod structure;
+mod text;
+pub mod tokenizer;
+pub mod tree;
+pub mod version;
 pub mod writer;
 
 pub type Result<T> = std::result::Result<T, QPdfError>;
 
-struct Handle(qpdf_sys::qpdf_data);
+// Page attributes which may be inherited from the page tree nodes
+const INHERITABLE_PAGE_KEYS: [&str; 4] = ["/Resources", "/MediaBox", "/CropBox", "/Rotate"];
+
+struct Handle {
+    handle: qpdf_sys::qpdf_data,
+    min_pdf_version: Option<String>,
+    // the buffer may be shared by the documents of several read attempts
+    buffer: Option<Rc<Vec<u8>>>,
+    // documents which foreign objects were copied from, QPDF reads their stream data when writing
+    foreign: RefCell<Vec<QPdf>>,
+    // stream data providers which are called when the document is written
+    providers: RefCell<Vec<StreamDataProvider>>,
+}
 
 impl Drop for Handle {
     fn drop(&mut self) {
         unsafe {
-            qpdf_sys::qpdf_cleanup(&mut self.0);
+            qpdf_sys::qpdf_cleanup(&mut self.handle);
         }
     }
 }
@@ -50,7 +103,7 @@ impl fmt::Debug for QPdf {
 
 impl QPdf {
     pub(crate) fn inner(&self) -> qpdf_sys::qpdf_data {
-        self.inner.0
+        self.inner.handle
     }
 
     fn wrap_ffi_call<F, R>(self: &QPdf, f: F) -> Result<()>
@@ -96,6 +149,13 @@ impl QPdf {
         }
     }
 
+    /// Release the native QPDF data immediately if this is the only reference to the document.
+    /// Every clone of `QPdf` and every object created from it holds a reference, if there are any
+    /// the document is returned back unchanged.
+    pub fn try_close(self) -> std::result::Result<(), QPdf> {
+        Rc::try_unwrap(self.inner).map(drop).map_err(|inner| QPdf { inner })
+    }
+
     /// Get QPDF library version
     pub fn library_version() -> String {
         unsafe {
@@ -106,12 +166,22 @@ impl QPdf {
     }
 
     fn new() -> QPdf {
+        QPdf::new_with_min_version(None)
+    }
+
+    fn new_with_min_version(min_pdf_version: Option<String>) -> QPdf {
         unsafe {
             let inner = qpdf_sys::qpdf_init();
             qpdf_sys::qpdf_set_suppress_warnings(inner, true.into());
             qpdf_sys::qpdf_silence_errors(inner);
             QPdf {
-                inner: Rc::new(Handle(inner)),
+                inner: Rc::new(Handle {
+                    handle: inner,
+                    min_pdf_version,
+                    buffer: None,
+                    foreign: RefCell::new(Vec::new()),
+                    providers: RefCell::new(Vec::new()),
+                }),
             }
         }
     }
@@ -125,6 +195,16 @@ impl QPdf {
         qpdf
     }
 
+    /// Create an empty PDF with the specified minimum version.
+    /// The version is applied to every writer created for this PDF unless overridden by `minimum_pdf_version`.
+    pub fn empty_with_version(version: &str) -> QPdf {
+        let qpdf = QPdf::new_with_min_version(Some(version.to_owned()));
+        unsafe {
+            qpdf_sys::qpdf_empty_pdf(qpdf.inner());
+        }
+        qpdf
+    }
+
     fn do_read_file(self: &QPdf, path: &Path, password: Option<&str>) -> Result<()> {
         let filename = CString::new(path.to_string_lossy().as_ref())?;
         let password = password.and_then(|p| CString::new(p).ok());
@@ -132,6 +212,7 @@ impl QPdf {
         let raw_password = password.as_ref().map(|p| p.as_ptr()).unwrap_or_else(ptr::null);
 
         self.wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_read(self.inner(), filename.as_ptr(), raw_password) })
+            .map_err(|e| e.with_password_required(password.as_ref().is_none_or(|p| p.as_bytes().is_empty())))
     }
 
     pub fn do_read_from_memory(self: &QPdf, buf: &[u8], password: Option<&str>) -> Result<()> {
@@ -148,6 +229,7 @@ impl QPdf {
                 raw_password,
             );
         })
+        .map_err(|e| e.with_password_required(password.as_ref().is_none_or(|p| p.as_bytes().is_empty())))
     }
 
     /// Read PDF from the file
@@ -164,18 +246,100 @@ impl QPdf {
         Ok(qpdf)
     }
 
-    /// Read PDF from memory
-    pub fn read_from_memory<T: AsRef<[u8]>>(buffer: T) -> Result<QPdf> {
+    /// Read damaged PDF from the file with recovery enabled and check it.
+    /// Return the document together with the warnings describing the damage found and recovered
+    /// during reading and checking.
+    pub fn read_with_recovery<P: AsRef<Path>>(path: P) -> Result<(QPdf, Vec<QPdfWarning>)> {
         let qpdf = QPdf::new();
-        qpdf.do_read_from_memory(buffer.as_ref(), None)?;
-        Ok(qpdf)
+        qpdf.enable_recovery(true);
+        qpdf.do_read_file(path.as_ref(), None)?;
+        qpdf.check_pdf()?;
+        let warnings = qpdf.take_warnings();
+        Ok((qpdf, warnings))
     }
 
-    /// Read encrypted PDF from memory
+    // Drain the warnings accumulated by QPDF since the last call
+    fn take_warnings(self: &QPdf) -> Vec<QPdfWarning> {
+        let mut warnings = Vec::new();
+        unsafe {
+            while qpdf_sys::qpdf_more_warnings(self.inner()) != 0 {
+                let warning = qpdf_sys::qpdf_next_warning(self.inner());
+                let detail = qpdf_sys::qpdf_get_error_message_detail(self.inner(), warning);
+                warnings.push(QPdfWarning {
+                    error_code: error_code_from_qpdf(qpdf_sys::qpdf_get_error_code(self.inner(), warning)),
+                    description: if !detail.is_null() {
+                        CStr::from_ptr(detail).to_string_lossy().into_owned()
+                    } else {
+                        String::new()
+                    },
+                    position: qpdf_sys::qpdf_get_error_file_position(self.inner(), warning),
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Check whether the buffer looks like a PDF file without parsing it: the `%PDF-` header must be
+    /// within the first 1024 bytes, as QPDF allows leading garbage, and `%%EOF` within the last 1024 bytes.
+    /// This is a cheap gate, a buffer which passes it may still fail to load.
+    pub fn is_pdf_bytes(buffer: &[u8]) -> bool {
+        let contains = |data: &[u8], pattern: &[u8]| data.windows(pattern.len()).any(|w| w == pattern);
+        let head = &buffer[..buffer.len().min(1024)];
+        let tail = &buffer[buffer.len().saturating_sub(1024)..];
+        contains(head, b"%PDF-") && contains(tail, b"%%EOF")
+    }
+
+    /// Read PDF from memory. The buffer is copied, QPDF reads the objects from it after the call returns.
+    pub fn read_from_memory<T: AsRef<[u8]>>(buffer: T) -> Result<QPdf> {
+        QPdf::read_from_owned_buffer(Rc::new(buffer.as_ref().to_vec()), None)
+    }
+
+    /// Read encrypted PDF from memory. The buffer is copied.
     pub fn read_from_memory_encrypted<T: AsRef<[u8]>>(buffer: T, password: &str) -> Result<QPdf> {
-        let qpdf = QPdf::new();
-        qpdf.do_read_from_memory(buffer.as_ref(), Some(password))?;
-        Ok(qpdf)
+        QPdf::read_from_owned_buffer(Rc::new(buffer.as_ref().to_vec()), Some(password))
+    }
+
+    /// Read encrypted PDF from the file, asking for passwords until the document is opened.
+    /// The document is first opened without a password, so an unencrypted document or one with an empty
+    /// user password is opened without calling `get_password`. Then while QPDF reports an invalid password
+    /// `get_password` is called with the zero-based attempt number. If it returns `None` the last error is returned.
+    /// The file is read only once and its data is shared by all attempts.
+    pub fn read_encrypted_retry<P, F>(path: P, mut get_password: F) -> Result<QPdf>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u32) -> Option<String>,
+    {
+        let buffer = Rc::new(std::fs::read(path)?);
+        let mut password = None;
+        let mut attempt = 0;
+        loop {
+            match QPdf::read_from_owned_buffer(buffer.clone(), password.as_deref()) {
+                Err(e) if e.error_code() == QPdfErrorCode::InvalidPassword => match get_password(attempt) {
+                    Some(next) => {
+                        password = Some(next);
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn read_from_owned_buffer(buffer: Rc<Vec<u8>>, password: Option<&str>) -> Result<QPdf> {
+        QPdf::new().read_owned_buffer(buffer, password)
+    }
+
+    // QPDF does not copy the memory buffer, so it is owned by the returned QPdf
+    fn read_owned_buffer(mut self: QPdf, buffer: Rc<Vec<u8>>, password: Option<&str>) -> Result<QPdf> {
+        if let Some(handle) = Rc::get_mut(&mut self.inner) {
+            handle.buffer = Some(buffer);
+        }
+        self.do_read_from_memory(
+            self.inner.buffer.as_deref().map(Vec::as_slice).unwrap_or_default(),
+            password,
+        )?;
+        Ok(self)
     }
 
     /// Return QPdfWriter used to write PDF to file or memory
@@ -183,11 +347,69 @@ impl QPdf {
         QPdfWriter::new(self.clone())
     }
 
+    /// Write PDF to memory without encryption. The document must have been opened with the correct password,
+    /// otherwise its strings and streams cannot be decrypted.
+    pub fn decrypt_to_memory(self: &QPdf) -> Result<Vec<u8>> {
+        self.writer().preserve_encryption(false).write_to_memory()
+    }
+
+    /// Write a linearized (web-optimized) copy of PDF to memory with compressed streams and generated
+    /// object streams. The encryption of the document, if any, is preserved.
+    pub fn linearize_to_memory(self: &QPdf) -> Result<Vec<u8>> {
+        self.writer()
+            .linearize(true)
+            .compress_streams(true)
+            .object_stream_mode(ObjectStreamMode::Generate)
+            .preserve_encryption(true)
+            .write_to_memory()
+    }
+
     /// Check PDF for errors
     pub fn check_pdf(self: &QPdf) -> Result<()> {
         self.wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_check_pdf(self.inner()) })
     }
 
+    /// Validate the document structure: the catalog must be a dictionary with `/Type /Catalog`,
+    /// its `/Pages` entry must be a dictionary with `/Type /Pages` and the `/Count` must match the number of pages.
+    pub fn validate_structure(self: &QPdf) -> Result<()> {
+        let structure_error = |description: &str| QPdfError::new(QPdfErrorCode::DamagedPdf, description.to_owned());
+
+        let root = self
+            .get_root()
+            .filter(|root| root.is_dictionary())
+            .ok_or_else(|| structure_error("Document catalog is missing or not a dictionary"))?;
+
+        if root.get("/Type").map(|t| t.as_name()).as_deref() != Some("/Catalog") {
+            return Err(structure_error("Document catalog does not have /Type /Catalog"));
+        }
+
+        let pages = root
+            .get("/Pages")
+            .filter(|pages| pages.is_dictionary())
+            .map(QPdfDictionary::from)
+            .ok_or_else(|| structure_error("/Pages entry is missing or not a dictionary"))?;
+
+        if pages.get("/Type").map(|t| t.as_name()).as_deref() != Some("/Pages") {
+            return Err(structure_error("Page tree root does not have /Type /Pages"));
+        }
+
+        let count = pages
+            .get("/Count")
+            .filter(|count| count.is_integer())
+            .map(|count| QPdfScalar::from(count).as_i64())
+            .ok_or_else(|| structure_error("Page tree root does not have a valid /Count"))?;
+
+        let num_pages = self.get_num_pages()?;
+        if count != num_pages as i64 {
+            return Err(structure_error(&format!(
+                "Page tree /Count is {} but the document has {} pages",
+                count, num_pages
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Enable or disable automatic PDF recovery
     pub fn enable_recovery(self: &QPdf, flag: bool) {
         unsafe { qpdf_sys::qpdf_set_attempt_recovery(self.inner(), flag.into()) }
@@ -226,7 +448,9 @@ impl QPdf {
     }
 
     /// Add a page object to PDF. The `first` parameter indicates whether to prepend or append it.
+    /// A page may belong to another PDF, in this case the other PDF is kept alive by this one.
     pub fn add_page<T: AsRef<QPdfObject>>(self: &QPdf, new_page: T, first: bool) -> Result<()> {
+        self.keep_foreign(&new_page.as_ref().owner);
         self.wrap_ffi_call(|| unsafe {
             qpdf_sys::qpdf_add_page(
                 self.inner(),
@@ -237,12 +461,59 @@ impl QPdf {
         })
     }
 
+    /// Add a blank page with the specified size in points. The `first` parameter indicates whether to prepend
+    /// or append it. Return the new page object.
+    pub fn add_blank_page(self: &QPdf, width: f64, height: f64, first: bool) -> Result<QPdfDictionary> {
+        let media_box = self.new_array_from([
+            self.new_number(0.0),
+            self.new_number(0.0),
+            self.new_number(width),
+            self.new_number(height),
+        ]);
+        let page: QPdfDictionary = self
+            .new_dictionary_from([
+                ("/Type", self.new_name("/Page")),
+                ("/MediaBox", media_box.into()),
+                ("/Resources", self.new_dictionary().into()),
+                ("/Contents", self.new_stream([]).into()),
+            ])
+            .into_indirect()
+            .into();
+        self.add_page(&page, first)?;
+        Ok(page)
+    }
+
+    /// Copy the page `index` of the `source` document into this one and add it as the first or the last page.
+    /// Return the new page which belongs to this document. The attributes which the page inherits from
+    /// the source page tree are set on the copy, and all objects referenced by the page are copied.
+    /// The source document is not modified, it is kept alive by this one to provide the stream data.
+    pub fn import_page(self: &QPdf, source: &QPdf, index: u32, first: bool) -> Result<QPdfDictionary> {
+        let source_page = source.get_page(index).ok_or_else(|| {
+            QPdfError::new(
+                QPdfErrorCode::PagesError,
+                format!("Page {} does not exist in the source document", index),
+            )
+        })?;
+
+        let page: QPdfDictionary = self.copy_from_foreign(&source_page).into();
+        for key in INHERITABLE_PAGE_KEYS {
+            if !source_page.has(key) {
+                if let Some(value) = source_page.get_inherited(key) {
+                    page.set(key, self.import_object(value));
+                }
+            }
+        }
+        self.add_page(&page, first)?;
+        Ok(page)
+    }
+
     /// Add a page object to PDF before or after a specified `ref_page`. A page may belong to another PDF.
     pub fn add_page_at<N, R>(self: &QPdf, new_page: N, before: bool, ref_page: R) -> Result<()>
     where
         N: AsRef<QPdfObject>,
         R: AsRef<QPdfObject>,
     {
+        self.keep_foreign(&new_page.as_ref().owner);
         self.wrap_ffi_call(|| unsafe {
             qpdf_sys::qpdf_add_page_at(
                 self.inner(),
@@ -262,6 +533,19 @@ impl QPdf {
         }
     }
 
+    /// Get the number of pages from the `/Root /Pages /Count` entry without building the page list.
+    /// Returns `None` if the entry is missing or is not a valid count. On malformed files the value
+    /// may differ from `get_num_pages`, which counts the actual page objects.
+    pub fn page_count_hint(self: &QPdf) -> Option<u32> {
+        let count = self
+            .get_root()?
+            .get("/Pages")
+            .filter(|pages| pages.is_dictionary())
+            .and_then(|pages| QPdfDictionary::from(pages).get("/Count"))
+            .filter(|count| count.is_integer())?;
+        u32::try_from(QPdfScalar::from(count).as_i64()).ok()
+    }
+
     /// Get a page object from the PDF with a given zero-based index
     pub fn get_page(self: &QPdf, zero_based_index: u32) -> Option<QPdfDictionary> {
         unsafe {
@@ -280,11 +564,94 @@ impl QPdf {
         Ok((0..self.get_num_pages()?).filter_map(|i| self.get_page(i)).collect())
     }
 
+    /// Return a lazy iterator over the pages of the PDF
+    pub fn pages(self: &QPdf) -> Result<QPdfPageIterator> {
+        Ok(QPdfPageIterator {
+            owner: self.clone(),
+            index: 0,
+            count: self.get_num_pages()?,
+        })
+    }
+
+    /// Build a new PDF from the pages with the given zero-based indices and write it to memory.
+    /// The pages are imported with `import_page`, so they keep their inherited attributes and this
+    /// document is not changed.
+    pub fn pages_to_memory(self: &QPdf, indices: &[u32]) -> Result<Vec<u8>> {
+        let num_pages = self.get_num_pages()?;
+        if let Some(index) = indices.iter().find(|&&index| index >= num_pages) {
+            return Err(QPdfError::new(
+                QPdfErrorCode::PagesError,
+                format!("Page index {} is out of range", index),
+            ));
+        }
+        let qpdf = QPdf::empty();
+        for &index in indices {
+            qpdf.import_page(self, index, false)?;
+        }
+        qpdf.writer().write_to_memory()
+    }
+
+    /// Create a new document from the pages in the zero-based `range`, which is clamped to the page count.
+    /// The pages are imported with `import_page`, so the objects shared between them are copied once.
+    pub fn extract_pages(self: &QPdf, range: std::ops::Range<u32>) -> Result<QPdf> {
+        let num_pages = self.get_num_pages()?;
+        let qpdf = QPdf::empty();
+        for index in range.start.min(num_pages)..range.end.min(num_pages) {
+            qpdf.import_page(self, index, false)?;
+        }
+        Ok(qpdf)
+    }
+
+    /// Get the length of the decoded page content in bytes. The C API has no counting pipeline,
+    /// so the content is still decoded by QPDF, but it is not copied into a Rust buffer.
+    pub fn page_content_length(self: &QPdf, page: &QPdfDictionary) -> Result<usize> {
+        Ok(page.get_page_content_data()?.len())
+    }
+
     /// Remove page object from the PDF.
     pub fn remove_page<P: AsRef<QPdfObject>>(self: &QPdf, page: P) -> Result<()> {
         self.wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_remove_page(self.inner(), page.as_ref().inner) })
     }
 
+    /// Move the page at `from_index` before the page which is currently at `to_index`.
+    /// A `to_index` equal to the number of pages moves the page to the end.
+    pub fn move_page(self: &QPdf, from_index: u32, to_index: u32) -> Result<()> {
+        let num_pages = self.get_num_pages()?;
+        let out_of_range = || {
+            QPdfError::new(
+                QPdfErrorCode::PagesError,
+                format!(
+                    "Cannot move page {} to {}, the document has {} pages",
+                    from_index, to_index, num_pages
+                ),
+            )
+        };
+        if to_index > num_pages {
+            return Err(out_of_range());
+        }
+
+        let page = self.get_page(from_index).ok_or_else(out_of_range)?;
+        if from_index == to_index || from_index + 1 == to_index {
+            return Ok(());
+        }
+
+        let ref_page = self.get_page(to_index);
+        self.remove_page(&page)?;
+        match ref_page {
+            Some(ref_page) => self.add_page_at(&page, true, &ref_page),
+            None => self.add_page(&page, false),
+        }
+    }
+
+    /// Reverse the order of pages
+    pub fn reverse_pages(self: &QPdf) -> Result<()> {
+        for page in self.get_pages()?.iter().skip(1) {
+            self.remove_page(page)?;
+            self.add_page(page, true)?;
+        }
+        Ok(())
+    }
+
     /// Parse textual representation of PDF object.
     pub fn parse_object(self: &QPdf, object: &str) -> Result<QPdfObject> {
         unsafe {
@@ -299,37 +666,253 @@ impl QPdf {
         let oh = unsafe { qpdf_sys::qpdf_get_trailer(self.inner()) };
         self.last_error_or_then(|| ()).ok()?;
         let obj = QPdfObject::new(self.clone(), oh);
-        if obj.get_type() != QPdfObjectType::Uninitialized && obj.get_type() != QPdfObjectType::Null {
+        if obj.get_type() != QPdfObjectType::Uninitialized && !obj.is_null() {
             Some(obj.into())
         } else {
             None
         }
     }
 
+    /// Set the trailer key. The cross-reference keys `/Size`, `/Prev` and `/XRefStm` are managed by QPDF
+    /// and cannot be set. `/Root` is expected to be a dictionary with `/Type /Catalog`, otherwise the written
+    /// file could not be loaded: the value is still set and a warning is returned.
+    pub fn set_trailer_key(self: &QPdf, key: &str, value: &QPdfObject) -> Result<Option<QPdfWarning>> {
+        if ["/Size", "/Prev", "/XRefStm"].contains(&key) {
+            return Err(QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                format!("Trailer key {} is managed by QPDF", key),
+            ));
+        }
+
+        let trailer = self
+            .get_trailer()
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::InvalidParameter, "Document has no trailer"))?;
+        trailer.set(key, value);
+
+        let is_catalog = value.is_dictionary()
+            && QPdfDictionary::from(value.clone())
+                .get("/Type")
+                .map(|t| t.as_name())
+                .as_deref()
+                == Some("/Catalog");
+        if key == "/Root" && !is_catalog {
+            return Ok(Some(QPdfWarning {
+                error_code: QPdfErrorCode::ObjectError,
+                description: "Trailer /Root is not a catalog dictionary".to_owned(),
+                position: 0,
+            }));
+        }
+        Ok(None)
+    }
+
     /// Get root object.
     pub fn get_root(self: &QPdf) -> Option<QPdfDictionary> {
         let oh = unsafe { qpdf_sys::qpdf_get_root(self.inner()) };
         self.last_error_or_then(|| ()).ok()?;
         let obj = QPdfObject::new(self.clone(), oh);
-        if obj.get_type() != QPdfObjectType::Uninitialized && obj.get_type() != QPdfObjectType::Null {
+        if obj.get_type() != QPdfObjectType::Uninitialized && !obj.is_null() {
             Some(obj.into())
         } else {
             None
         }
     }
 
+    fn get_root_or_error(self: &QPdf) -> Result<QPdfDictionary> {
+        self.get_root()
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::ObjectError, "Document catalog is missing"))
+    }
+
+    /// Set `/ViewerPreferences /PrintScaling` to `/None` so that pages are printed without scaling
+    pub fn disable_print_scaling(self: &QPdf) -> Result<()> {
+        let root = self.get_root_or_error()?;
+        let prefs: QPdfDictionary = match root.get("/ViewerPreferences") {
+            Some(prefs) if prefs.is_dictionary() => prefs.into(),
+            _ => {
+                let prefs = self.new_dictionary();
+                root.set("/ViewerPreferences", &prefs);
+                prefs
+            }
+        };
+        prefs.set("/PrintScaling", self.new_name("/None"));
+        Ok(())
+    }
+
+    /// Set `/AcroForm /NeedAppearances` so that viewers regenerate the appearances of the form fields,
+    /// for example after their values were changed. An empty `/AcroForm` is created if absent.
+    pub fn set_need_appearances(self: &QPdf, flag: bool) -> Result<()> {
+        let root = self.get_root_or_error()?;
+        let acro_form: QPdfDictionary = match root.get("/AcroForm") {
+            Some(acro_form) if acro_form.is_dictionary() => acro_form.into(),
+            _ => {
+                let acro_form = self.new_dictionary_from([("/Fields", self.new_array())]);
+                root.set("/AcroForm", &acro_form);
+                acro_form
+            }
+        };
+        acro_form.set("/NeedAppearances", self.new_bool(flag));
+        Ok(())
+    }
+
+    /// Configure the viewer to display pages as two-page spreads. When `cover_separate` is true
+    /// the first page is displayed alone (`/PageLayout /TwoPageRight`), otherwise `/TwoPageLeft` layout is used.
+    pub fn set_two_page_spread(self: &QPdf, cover_separate: bool) -> Result<()> {
+        let layout = if cover_separate {
+            "/TwoPageRight"
+        } else {
+            "/TwoPageLeft"
+        };
+        self.get_root_or_error()?.set("/PageLayout", self.new_name(layout));
+        Ok(())
+    }
+
+    /// Configure the viewer to display one page at a time (`/PageLayout /SinglePage`)
+    pub fn set_single_page_layout(self: &QPdf) -> Result<()> {
+        self.get_root_or_error()?
+            .set("/PageLayout", self.new_name("/SinglePage"));
+        Ok(())
+    }
+
+    /// Return `Some(cover_separate)` if the document is configured to display two-page spreads, `None` otherwise
+    pub fn get_two_page_spread(self: &QPdf) -> Option<bool> {
+        let layout = self.get_root()?.get("/PageLayout")?;
+        if !layout.is_name() {
+            return None;
+        }
+        match layout.as_name().as_str() {
+            "/TwoPageLeft" | "/TwoColumnLeft" => Some(false),
+            "/TwoPageRight" | "/TwoColumnRight" => Some(true),
+            _ => None,
+        }
+    }
+
     /// Find indirect object by object id and generation
     pub fn get_object_by_id(self: &QPdf, obj_id: u32, gen: u32) -> Option<QPdfObject> {
         let oh = unsafe { qpdf_sys::qpdf_get_object_by_id(self.inner(), obj_id as _, gen as _) };
         self.last_error_or_then(|| ()).ok()?;
         let obj = QPdfObject::new(self.clone(), oh);
-        if obj.get_type() != QPdfObjectType::Uninitialized && obj.get_type() != QPdfObjectType::Null {
+        if obj.get_type() != QPdfObjectType::Uninitialized && !obj.is_null() {
             Some(obj)
         } else {
             None
         }
     }
 
+    /// Return all indirect objects of the document, ordered by object id and generation. The objects reachable
+    /// from the trailer are collected first, then the remaining object ids below the trailer `/Size` are looked up
+    /// with generation 0, which finds the unreferenced objects of the cross-reference table. Unreferenced objects
+    /// created after the document was read are not included.
+    pub fn get_all_objects(self: &QPdf) -> Vec<QPdfObject> {
+        let mut visited = HashSet::new();
+        let mut objects = Vec::new();
+        let mut queue: Vec<QPdfObject> = self.get_trailer().into_iter().map(Into::into).collect();
+
+        while let Some(obj) = queue.pop() {
+            if obj.is_indirect() {
+                if !visited.insert((obj.get_id(), obj.get_generation())) {
+                    continue;
+                }
+                objects.push(obj.clone());
+            }
+            match obj.get_type() {
+                QPdfObjectType::Array => queue.extend(QPdfArray::from(obj).iter()),
+                QPdfObjectType::Dictionary => {
+                    let dict = QPdfDictionary::from(obj);
+                    queue.extend(dict.keys().iter().filter_map(|key| dict.get(key)));
+                }
+                QPdfObjectType::Stream => queue.push(QPdfStream::from(obj).get_dictionary().into()),
+                _ => {}
+            }
+        }
+
+        let size = self
+            .get_trailer()
+            .and_then(|trailer| trailer.get("/Size"))
+            .and_then(|size| size.as_number())
+            .unwrap_or_default() as u32;
+        let reachable = visited.iter().map(|(id, _)| *id).collect::<HashSet<_>>();
+        for id in (1..size).filter(|id| !reachable.contains(id)) {
+            objects.extend(self.get_object_by_id(id, 0));
+        }
+
+        objects.sort_by_key(|obj| (obj.get_id(), obj.get_generation()));
+        objects
+    }
+
+    /// Return the number of indirect objects returned by `get_all_objects`
+    pub fn object_count(self: &QPdf) -> usize {
+        self.get_all_objects().len()
+    }
+
+    /// Recompress the data of all streams in place. `Compress` encodes the streams which have no filters
+    /// or only generalized filters with `/FlateDecode`, `Uncompress` removes the generalized filters
+    /// and `Preserve` leaves the streams unchanged. Streams with other filters are not modified.
+    /// The streams are encoded by the QPDF writer: their data is written to a temporary document
+    /// in memory with the given stream data mode and read back.
+    pub fn recompress_streams(self: &QPdf, mode: StreamDataMode) -> Result<()> {
+        if mode == StreamDataMode::Preserve {
+            return Ok(());
+        }
+
+        let streams = self
+            .get_all_objects()
+            .into_iter()
+            .filter(|obj| obj.is_stream())
+            .map(QPdfStream::from)
+            .filter(|stream| match stream.get_filters() {
+                Some(filters) if filters.iter().all(|filter| filter.is_generalized()) => match mode {
+                    StreamDataMode::Compress => filters != [StreamFilter::Flate],
+                    _ => !filters.is_empty(),
+                },
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+        if streams.is_empty() {
+            return Ok(());
+        }
+
+        let temp = QPdf::empty();
+        let copies = temp.new_array();
+        for stream in &streams {
+            let dict = stream.get_dictionary();
+            let entries = ["/Filter", "/DecodeParms"]
+                .into_iter()
+                .filter_map(|key| dict.get(key).map(|value| (key, temp.import_object(value))));
+            let copy = temp.new_stream_with_dictionary(entries, stream.get_data(StreamDecodeLevel::None)?);
+            copies.push(copy.into_indirect());
+        }
+        let root = temp
+            .get_root()
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::InternalError, "Temporary document has no catalog"))?;
+        root.set("/Streams", &copies);
+
+        let written = QPdf::read_from_memory(
+            temp.writer()
+                .stream_data_mode(mode)
+                .object_stream_mode(ObjectStreamMode::Disable)
+                .write_to_memory()?,
+        )?;
+        let copies: QPdfArray = written
+            .get_root()
+            .and_then(|root| root.get("/Streams"))
+            .filter(|copies| copies.is_array())
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::InternalError, "Recompressed streams are missing"))?
+            .into();
+
+        for (stream, copy) in streams.iter().zip(copies.iter()) {
+            let copy = QPdfStream::from(copy);
+            let dict = copy.get_dictionary();
+            let entry = |key: &str| {
+                dict.get(key)
+                    .map(|value| self.import_object(value))
+                    .unwrap_or_else(|| self.new_null())
+            };
+            let data = copy.get_data(StreamDecodeLevel::None)?;
+            self.replace_raw_stream_data(stream, &data, &entry("/Filter"), &entry("/DecodeParms"))?;
+        }
+
+        Ok(())
+    }
+
     /// Replace indirect object by object id and generation
     pub fn replace_object<O: AsRef<QPdfObject>>(self: &QPdf, obj_id: u32, gen: u32, object: O) -> Result<()> {
         self.wrap_ffi_call(|| unsafe {
@@ -337,6 +920,127 @@ impl QPdf {
         })
     }
 
+    /// Replace the indirect object `target` with `replacement`, which must be a direct object.
+    /// Handles to `target` obtained before the call may still see the old value, use `resolve` to get the new one.
+    pub fn replace<O: AsRef<QPdfObject>>(self: &QPdf, target: &QPdfObject, replacement: O) -> Result<()> {
+        if !target.is_indirect() || !Rc::ptr_eq(&target.owner.inner, &self.inner) {
+            return Err(QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                "Replaced object must be an indirect object of this document",
+            ));
+        }
+        self.replace_object(target.get_id(), target.get_generation(), replacement)
+    }
+
+    /// Swap the definitions of two indirect objects. All references to the first object will resolve
+    /// to the second one and vice versa. Two streams are swapped in place: their dictionaries and
+    /// the raw encoded data are exchanged, keeping the filters. A stream cannot be swapped with
+    /// an object of another type.
+    pub fn swap_objects(self: &QPdf, id1: u32, gen1: u32, id2: u32, gen2: u32) -> Result<()> {
+        let first = self.get_existing_object(id1, gen1)?;
+        let second = self.get_existing_object(id2, gen2)?;
+
+        match (first.is_stream(), second.is_stream()) {
+            (true, true) => {
+                let first = QPdfStream::from(first);
+                let second = QPdfStream::from(second);
+                let first_data = first.get_raw_data()?.to_vec();
+                let second_data = second.get_raw_data()?.to_vec();
+                let first_entries = first.get_dictionary().raw_entries();
+                let second_entries = second.get_dictionary().raw_entries();
+                self.replace_stream_contents(&first, &second_data, &second_entries)?;
+                self.replace_stream_contents(&second, &first_data, &first_entries)
+            }
+            (false, false) => {
+                let first = self.shallow_copy_of(first)?;
+                let second = self.shallow_copy_of(second)?;
+                self.replace_object(id1, gen1, &second)?;
+                self.replace_object(id2, gen2, &first)
+            }
+            _ => Err(QPdfError::new(
+                QPdfErrorCode::ObjectError,
+                "A stream can only be swapped with another stream",
+            )),
+        }
+    }
+
+    fn get_existing_object(self: &QPdf, obj_id: u32, gen: u32) -> Result<QPdfObject> {
+        self.get_object_by_id(obj_id, gen).ok_or_else(|| {
+            QPdfError::new(
+                QPdfErrorCode::ObjectError,
+                format!("Object {} {} R does not exist", obj_id, gen),
+            )
+        })
+    }
+
+    fn shallow_copy_of(self: &QPdf, obj: QPdfObject) -> Result<QPdfObject> {
+        match obj.get_type() {
+            QPdfObjectType::Dictionary => {
+                let dict = self.new_dictionary();
+                for (key, value) in QPdfDictionary::from(obj).raw_entries() {
+                    dict.set(&key, &value);
+                }
+                Ok(dict.into())
+            }
+            QPdfObjectType::Array => Ok(self.new_array_from(QPdfArray::from(obj).iter()).into()),
+            _ => {
+                let copy = obj.clone();
+                self.wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_oh_make_direct(self.inner(), copy.inner) })?;
+                Ok(copy)
+            }
+        }
+    }
+
+    // Replace the dictionary and the data of the stream, the data is encoded with the filters from the entries
+    fn replace_stream_contents(
+        self: &QPdf,
+        stream: &QPdfStream,
+        raw_data: &[u8],
+        entries: &[(String, QPdfObject)],
+    ) -> Result<()> {
+        let dict = stream.get_dictionary();
+        for key in dict.keys() {
+            dict.remove(&key);
+        }
+        for (key, value) in entries.iter().filter(|(key, _)| key != "/Length") {
+            dict.set(key, value);
+        }
+        let entry = |name: &str| {
+            entries
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_else(|| self.new_null())
+        };
+        self.replace_raw_stream_data(stream, raw_data, &entry("/Filter"), &entry("/DecodeParms"))
+    }
+
+    // Replace the data of the stream, the data is encoded with the given /Filter and /DecodeParms values
+    fn replace_raw_stream_data(
+        self: &QPdf,
+        stream: &QPdfStream,
+        raw_data: &[u8],
+        filter: &QPdfObject,
+        params: &QPdfObject,
+    ) -> Result<()> {
+        self.wrap_ffi_call(|| unsafe {
+            qpdf_sys::qpdf_oh_replace_stream_data(
+                self.inner(),
+                stream.as_object().inner,
+                raw_data.as_ptr() as _,
+                raw_data.len() as _,
+                filter.inner,
+                params.inner,
+            )
+        })
+    }
+
+    /// Create an object from a Rust value: booleans, integers, floats, strings, vectors of those,
+    /// or an existing object
+    pub fn obj<T: IntoQPdfObject>(self: &QPdf, value: T) -> QPdfObject {
+        value.into_qpdf_object(self)
+    }
+
     /// Create a bool object
     pub fn new_bool(self: &QPdf, value: bool) -> QPdfObject {
         let oh = unsafe { qpdf_sys::qpdf_oh_new_bool(self.inner(), value.into()) };
@@ -355,6 +1059,18 @@ impl QPdf {
         QPdfObject::new(self.clone(), oh).into()
     }
 
+    /// Create an integer object from the unsigned value. QPDF stores integers as signed 64-bit values,
+    /// so values above `i64::MAX` result in `QPdfErrorCode::InvalidParameter`.
+    pub fn new_integer_u64(self: &QPdf, value: u64) -> Result<QPdfScalar> {
+        let value = i64::try_from(value).map_err(|_| {
+            QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                format!("Integer {} does not fit into a PDF integer object", value),
+            )
+        })?;
+        Ok(self.new_integer(value))
+    }
+
     /// Create a real object from the textual representation
     pub fn new_real_from_string(self: &QPdf, value: &str) -> QPdfScalar {
         let oh = unsafe {
@@ -370,6 +1086,15 @@ impl QPdf {
         QPdfObject::new(self.clone(), oh).into()
     }
 
+    // Create an integer object for whole values and a real object otherwise
+    pub(crate) fn new_number(self: &QPdf, value: f64) -> QPdfObject {
+        if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+            self.new_integer(value as i64).into()
+        } else {
+            self.new_real(value, 4).into()
+        }
+    }
+
     /// Create an empty array object
     pub fn new_array(self: &QPdf) -> QPdfArray {
         let oh = unsafe { qpdf_sys::qpdf_oh_new_array(self.inner()) };
@@ -389,26 +1114,44 @@ impl QPdf {
         array
     }
 
-    /// Create a name object
+    /// Create a name object. Panics if the value contains a null character, use `try_new_name`
+    /// for the untrusted input.
     pub fn new_name(self: &QPdf, value: &str) -> QPdfObject {
-        let oh = unsafe {
-            let value_str = CString::new(value).unwrap();
-            qpdf_sys::qpdf_oh_new_name(self.inner(), value_str.as_ptr())
-        };
-        QPdfObject::new(self.clone(), oh)
+        self.try_new_name(value).unwrap()
+    }
+
+    /// Create a name object, a null character in the value results in `QPdfErrorCode::InvalidParameter`
+    pub fn try_new_name(self: &QPdf, value: &str) -> Result<QPdfObject> {
+        let value_str = CString::new(value)?;
+        let oh = unsafe { qpdf_sys::qpdf_oh_new_name(self.inner(), value_str.as_ptr()) };
+        Ok(QPdfObject::new(self.clone(), oh))
     }
 
-    /// Create a string object encoded as a PDF string or binary string
+    /// Create a name object validating it first, see `QPdfName` for the details
+    pub fn new_name_checked(self: &QPdf, value: &str) -> Result<QPdfObject> {
+        Ok(QPdfName::new(value)?.to_object(self))
+    }
+
+    /// Create a string object encoded as a PDF string or binary string. Panics if the value contains
+    /// a null character, use `try_new_utf8_string` for the untrusted input.
     pub fn new_utf8_string(self: &QPdf, value: &str) -> QPdfObject {
-        let oh = unsafe {
-            let value_str = CString::new(value).unwrap();
-            qpdf_sys::qpdf_oh_new_unicode_string(self.inner(), value_str.as_ptr())
-        };
-        QPdfObject::new(self.clone(), oh)
+        self.try_new_utf8_string(value).unwrap()
     }
 
-    /// Create a PDF string object enclosed in parentheses
+    /// Create a string object encoded as a PDF string or binary string,
+    /// a null character in the value results in `QPdfErrorCode::InvalidParameter`
+    pub fn try_new_utf8_string(self: &QPdf, value: &str) -> Result<QPdfObject> {
+        let value_str = CString::new(value)?;
+        let oh = unsafe { qpdf_sys::qpdf_oh_new_unicode_string(self.inner(), value_str.as_ptr()) };
+        Ok(QPdfObject::new(self.clone(), oh))
+    }
+
+    /// Create a PDF string object enclosed in parentheses.
+    /// PDF strings may contain null characters, such values are passed to QPDF with their length.
     pub fn new_string(self: &QPdf, value: &str) -> QPdfObject {
+        if value.contains('\0') {
+            return self.new_binary_string(value);
+        }
         let oh = unsafe {
             let value_str = CString::new(value).unwrap();
             qpdf_sys::qpdf_oh_new_string(self.inner(), value_str.as_ptr())
@@ -449,7 +1192,7 @@ impl QPdf {
     pub fn new_stream<D: AsRef<[u8]>>(self: &QPdf, data: D) -> QPdfStream {
         let oh = unsafe { qpdf_sys::qpdf_oh_new_stream(self.inner()) };
         let obj: QPdfStream = QPdfObject::new(self.clone(), oh).into();
-        obj.replace_data(data, &self.new_null(), &self.new_null());
+        obj.replace_data(data, self.new_null(), self.new_null());
         obj
     }
 
@@ -470,11 +1213,66 @@ impl QPdf {
         stream
     }
 
-    /// Copy object from the foreign PDF
+    /// Copy object from the foreign PDF. The foreign PDF is kept alive by this one.
     pub fn copy_from_foreign<F: AsRef<QPdfObject>>(self: &QPdf, foreign: F) -> QPdfObject {
+        self.keep_foreign(&foreign.as_ref().owner);
         let oh = unsafe {
             qpdf_sys::qpdf_oh_copy_foreign_object(self.inner(), foreign.as_ref().owner.inner(), foreign.as_ref().inner)
         };
         QPdfObject::new(self.clone(), oh)
     }
+
+    // Keep the foreign document alive until this one is dropped
+    fn keep_foreign(self: &QPdf, owner: &QPdf) {
+        let mut foreign = self.inner.foreign.borrow_mut();
+        if !Rc::ptr_eq(&self.inner, &owner.inner) && !foreign.iter().any(|doc| Rc::ptr_eq(&doc.inner, &owner.inner)) {
+            foreign.push(owner.clone());
+        }
+    }
+
+    /// Create a new document which contains the pages of all documents in order.
+    /// The pages are copied as foreign objects together with their resources such as fonts and images.
+    pub fn merge<I: IntoIterator<Item = QPdf>>(docs: I) -> Result<QPdf> {
+        let merged = QPdf::empty();
+        for doc in docs {
+            for page in doc.get_pages()? {
+                merged.add_page(&page, false)?;
+            }
+        }
+        Ok(merged)
+    }
+}
+
+/// Lazy iterator over the PDF pages
+pub struct QPdfPageIterator {
+    owner: QPdf,
+    index: u32,
+    count: u32,
+}
+
+impl Iterator for QPdfPageIterator {
+    type Item = QPdfDictionary;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.count {
+            let page = self.owner.get_page(self.index);
+            self.index += 1;
+            page
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index = self
+            .index
+            .saturating_add(u32::try_from(n).unwrap_or(u32::MAX))
+            .min(self.count);
+        self.next()
+    }
 }
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use crate::{QPdf, QPdfError, QPdfErrorCode, QPdfObject, Result};
+
+/// QPdfName holds a validated PDF name including the leading slash.
+/// The value is kept in the unescaped form, `#xx` escape sequences are applied when the name is written.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QPdfName {
+    name: String,
+}
+
+impl QPdfName {
+    /// Create a name from the string which must start with a slash. Escape sequences in the `#xx` form
+    /// are decoded, unescaped special characters such as spaces are accepted and escaped on output.
+    pub fn new(name: &str) -> Result<QPdfName> {
+        if !name.starts_with('/') {
+            return Err(invalid_name(name, "name must start with a slash"));
+        }
+
+        let mut decoded = Vec::with_capacity(name.len());
+        let mut bytes = name.bytes();
+        while let Some(ch) = bytes.next() {
+            match ch {
+                0 => return Err(invalid_name(name, "name must not contain null characters")),
+                b'#' => {
+                    let hex = [bytes.next(), bytes.next()];
+                    let value = match hex {
+                        [Some(hi), Some(lo)] => std::str::from_utf8(&[hi, lo])
+                            .ok()
+                            .and_then(|s| u8::from_str_radix(s, 16).ok()),
+                        _ => None,
+                    };
+                    match value {
+                        Some(0) | None => return Err(invalid_name(name, "invalid escape sequence")),
+                        Some(value) => decoded.push(value),
+                    }
+                }
+                ch => decoded.push(ch),
+            }
+        }
+
+        let name = String::from_utf8(decoded).map_err(|_| invalid_name(name, "name is not a valid UTF-8 string"))?;
+        Ok(QPdfName { name })
+    }
+
+    /// Return the unescaped name
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the name with special characters escaped according to the PDF specification
+    pub fn escaped(&self) -> String {
+        let mut result = String::with_capacity(self.name.len());
+        for (i, ch) in self.name.bytes().enumerate() {
+            if i > 0 && (b"#()<>[]{}/%".contains(&ch) || !(33..=126).contains(&ch)) {
+                result.push_str(&format!("#{:02x}", ch));
+            } else {
+                result.push(ch as char);
+            }
+        }
+        result
+    }
+
+    /// Create a name object owned by the given `QPdf`
+    pub fn to_object(&self, qpdf: &QPdf) -> QPdfObject {
+        qpdf.new_name(&self.name)
+    }
+}
+
+fn invalid_name(name: &str, reason: &str) -> QPdfError {
+    QPdfError::new(
+        QPdfErrorCode::InvalidParameter,
+        format!("Invalid name {:?}: {}", name, reason),
+    )
+}
+
+impl AsRef<str> for QPdfName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for QPdfName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.escaped())
+    }
+}
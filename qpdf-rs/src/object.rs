@@ -1,6 +1,14 @@
-use std::{cmp::Ordering, ffi::CStr, fmt, slice};
-
-use crate::QPdf;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashSet},
+    ffi::CStr,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+    slice,
+};
+
+use crate::{Handle, QPdf, QPdfArray, QPdfDictionary, QPdfScalar, QPdfStream, Result, StreamDecodeLevel};
 
 /// Types of the QPDF objects
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
@@ -39,12 +47,112 @@ impl QPdfObjectType {
             _ => panic!("Unexpected object type!"),
         }
     }
+
+    /// Return the lowercase type name as used by qpdf, for example `dictionary` or `inlineimage`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QPdfObjectType::Uninitialized => "uninitialized",
+            QPdfObjectType::Reserved => "reserved",
+            QPdfObjectType::Null => "null",
+            QPdfObjectType::Boolean => "boolean",
+            QPdfObjectType::Integer => "integer",
+            QPdfObjectType::Real => "real",
+            QPdfObjectType::String => "string",
+            QPdfObjectType::Name => "name",
+            QPdfObjectType::Array => "array",
+            QPdfObjectType::Dictionary => "dictionary",
+            QPdfObjectType::Stream => "stream",
+            QPdfObjectType::Operator => "operator",
+            QPdfObjectType::InlineImage => "inlineimage",
+        }
+    }
+}
+
+impl fmt::Display for QPdfObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Conversion of Rust values into PDF objects of the given document, see `QPdf::obj`
+pub trait IntoQPdfObject {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject;
+}
+
+impl IntoQPdfObject for QPdfObject {
+    fn into_qpdf_object(self, _owner: &QPdf) -> QPdfObject {
+        self
+    }
+}
+
+impl IntoQPdfObject for bool {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner.new_bool(self)
+    }
+}
+
+impl IntoQPdfObject for i32 {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner.new_integer(self.into()).into()
+    }
+}
+
+impl IntoQPdfObject for u32 {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner.new_integer(self.into()).into()
+    }
+}
+
+impl IntoQPdfObject for i64 {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner.new_integer(self).into()
+    }
+}
+
+/// Whole numbers are stored as integers, other values as reals with 4 decimal places
+impl IntoQPdfObject for f64 {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner.new_number(self)
+    }
+}
+
+// A string with a null character cannot be passed to QPDF as a C string,
+// so it is stored as a UTF-16BE binary string, which keeps the text intact
+fn utf8_string_object(owner: &QPdf, value: &str) -> QPdfObject {
+    owner.try_new_utf8_string(value).unwrap_or_else(|_| {
+        let mut data = vec![0xfe, 0xff];
+        data.extend(value.encode_utf16().flat_map(u16::to_be_bytes));
+        owner.new_binary_string(data)
+    })
+}
+
+/// Strings are converted into literal strings, use `QPdf::new_name` for names.
+/// A string with a null character is converted into a UTF-16BE binary string.
+impl IntoQPdfObject for &str {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        utf8_string_object(owner, self)
+    }
+}
+
+impl IntoQPdfObject for String {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        utf8_string_object(owner, &self)
+    }
+}
+
+impl<T: IntoQPdfObject> IntoQPdfObject for Vec<T> {
+    fn into_qpdf_object(self, owner: &QPdf) -> QPdfObject {
+        owner
+            .new_array_from(self.into_iter().map(|item| item.into_qpdf_object(owner)))
+            .into()
+    }
 }
 
 pub trait QPdfObjectLike {
     /// Return inner object
     fn as_object(&self) -> &QPdfObject;
 
+    /// Return the `QPdf` which owns this object
     fn owner(&self) -> QPdf {
         self.as_object().owner.clone()
     }
@@ -74,6 +182,66 @@ pub trait QPdfObjectLike {
         self.as_object().is_indirect()
     }
 
+    /// Return true if this is a null object
+    fn is_null(&self) -> bool {
+        self.as_object().is_null()
+    }
+
+    /// Return true if this is a boolean object
+    fn is_bool(&self) -> bool {
+        self.as_object().is_bool()
+    }
+
+    /// Return true if this is a integer object
+    fn is_integer(&self) -> bool {
+        self.as_object().is_integer()
+    }
+
+    /// Return true if this is a name object
+    fn is_name(&self) -> bool {
+        self.as_object().is_name()
+    }
+
+    /// Return true if this is a string object
+    fn is_string(&self) -> bool {
+        self.as_object().is_string()
+    }
+
+    /// Return true if this is a array object
+    fn is_array(&self) -> bool {
+        self.as_object().is_array()
+    }
+
+    /// Return true if this is a dictionary object
+    fn is_dictionary(&self) -> bool {
+        self.as_object().is_dictionary()
+    }
+
+    /// Return true if this is a stream object
+    fn is_stream(&self) -> bool {
+        self.as_object().is_stream()
+    }
+
+    /// Convert to a stream, `None` if this is not a stream object
+    fn as_stream(&self) -> Option<QPdfStream> {
+        self.is_stream().then(|| self.as_object().clone().into())
+    }
+
+    /// Convert to a dictionary, `None` if this is not a dictionary object
+    fn as_dictionary(&self) -> Option<QPdfDictionary> {
+        self.is_dictionary().then(|| self.as_object().clone().into())
+    }
+
+    /// Convert to an array, `None` if this is not an array object
+    fn as_array(&self) -> Option<QPdfArray> {
+        self.is_array().then(|| self.as_object().clone().into())
+    }
+
+    /// Convert to a scalar, `None` for arrays, dictionaries and streams
+    fn as_scalar(&self) -> Option<QPdfScalar> {
+        self.is_scalar().then(|| self.as_object().clone().into())
+    }
+
     /// Get boolean value
     fn as_bool(&self) -> bool {
         self.as_object().as_bool()
@@ -84,7 +252,12 @@ pub trait QPdfObjectLike {
         self.as_object().as_name()
     }
 
-    /// Get string value
+    /// Get numeric value of the integer or real object, `None` for other object types
+    fn as_number(&self) -> Option<f64> {
+        self.as_object().as_number()
+    }
+
+    /// Get string value decoded from PDFDocEncoding or UTF-16 to UTF-8
     fn as_string(&self) -> String {
         self.as_object().as_string()
     }
@@ -94,6 +267,14 @@ pub trait QPdfObjectLike {
         self.as_object().as_binary_string()
     }
 
+    /// Get the string bytes exactly as stored in the document, without decoding. `as_string` is meant
+    /// for the text strings such as `/Info` entries or `/Title`, the decoding loses the original bytes
+    /// and replaces the invalid sequences. Prefer this method for byte strings such as `/ID`,
+    /// hashes or content stream operands, and when the original encoding must be preserved.
+    fn as_raw_string(&self) -> Vec<u8> {
+        self.as_binary_string()
+    }
+
     /// Get ID of the indirect object
     fn get_id(&self) -> u32 {
         self.as_object().get_id()
@@ -104,6 +285,25 @@ pub trait QPdfObjectLike {
         self.as_object().get_generation()
     }
 
+    /// Return the object this indirect reference points to, looked up by its ID and generation,
+    /// or a null object if it does not exist. For a direct object return a clone.
+    fn resolve(&self) -> QPdfObject {
+        let obj = self.as_object();
+        if obj.is_indirect() {
+            obj.owner
+                .get_object_by_id(obj.get_id(), obj.get_generation())
+                .unwrap_or_else(|| obj.owner.new_null())
+        } else {
+            obj.clone()
+        }
+    }
+
+    /// Return a direct copy of this object with all indirect references inside it resolved recursively.
+    /// The operation fails if the object is or contains a stream, or if it has circular references.
+    fn make_direct(&self) -> Result<QPdfObject> {
+        self.as_object().make_direct()
+    }
+
     fn into_indirect(self) -> QPdfObject
     where
         Self: Sized + Into<QPdfObject>,
@@ -123,6 +323,162 @@ impl QPdfObject {
     pub(crate) fn new(owner: QPdf, inner: qpdf_sys::qpdf_oh) -> Self {
         QPdfObject { owner, inner }
     }
+
+    // Owner document, object id and generation for the indirect objects or the handle for the direct ones
+    fn identity(&self) -> (*const Handle, u32, u32, qpdf_sys::qpdf_oh) {
+        let owner = Rc::as_ptr(&self.owner.inner);
+        if self.is_indirect() {
+            (owner, self.get_id(), self.get_generation(), 0)
+        } else {
+            (owner, 0, 0, self.inner)
+        }
+    }
+
+    /// Return the `QPdf` which owns this object. The returned value shares the same underlying document,
+    /// so the objects created with it can be used together with this object.
+    pub fn owner(&self) -> QPdf {
+        self.owner.clone()
+    }
+
+    /// Compare object values recursively rather than object handles. Indirect references are followed,
+    /// dictionary keys with null values are treated as absent and streams are compared by their dictionaries
+    /// and raw data. The objects may belong to different documents.
+    pub fn structural_eq(&self, other: &QPdfObject) -> bool {
+        self.structural_eq_impl(other, &mut HashSet::new())
+    }
+
+    fn structural_eq_impl(&self, other: &QPdfObject, visited: &mut HashSet<(ObjGen, ObjGen)>) -> bool {
+        if self.is_indirect() && other.is_indirect() {
+            let pair = (
+                (self.get_id(), self.get_generation()),
+                (other.get_id(), other.get_generation()),
+            );
+            // the pair is already being compared up the stack
+            if !visited.insert(pair) {
+                return true;
+            }
+        }
+
+        let obj_type = self.get_type();
+        if obj_type != other.get_type() {
+            return false;
+        }
+
+        match obj_type {
+            QPdfObjectType::Boolean => self.as_bool() == other.as_bool(),
+            QPdfObjectType::Integer => {
+                QPdfScalar::from(self.clone()).as_i64() == QPdfScalar::from(other.clone()).as_i64()
+            }
+            QPdfObjectType::Real => QPdfScalar::from(self.clone()).as_f64() == QPdfScalar::from(other.clone()).as_f64(),
+            QPdfObjectType::String => self.as_binary_string() == other.as_binary_string(),
+            QPdfObjectType::Name => self.as_name() == other.as_name(),
+            QPdfObjectType::Array => {
+                let (array, other_array) = (QPdfArray::from(self.clone()), QPdfArray::from(other.clone()));
+                array.len() == other_array.len()
+                    && array
+                        .iter()
+                        .zip(other_array.iter())
+                        .all(|(item, other_item)| item.structural_eq_impl(&other_item, visited))
+            }
+            QPdfObjectType::Dictionary => {
+                let (dict, other_dict) = (QPdfDictionary::from(self.clone()), QPdfDictionary::from(other.clone()));
+                dict_structural_eq(&dict, &other_dict, visited)
+            }
+            QPdfObjectType::Stream => {
+                let (stream, other_stream) = (QPdfStream::from(self.clone()), QPdfStream::from(other.clone()));
+                dict_structural_eq(&stream.get_dictionary(), &other_stream.get_dictionary(), visited)
+                    && match (
+                        stream.get_data(StreamDecodeLevel::None),
+                        other_stream.get_data(StreamDecodeLevel::None),
+                    ) {
+                        (Ok(data), Ok(other_data)) => data.as_ref() == other_data.as_ref(),
+                        _ => false,
+                    }
+            }
+            _ => self.unparse_value() == other.unparse_value(),
+        }
+    }
+
+    /// Unparse the object inlining the content of the indirect objects up to `max_depth` levels of references,
+    /// the references deeper than that are printed as `N G R`. This is intended for debugging and log output.
+    /// A reference to an object which is already being expanded is printed as `... (cycle)`.
+    pub fn unparse_resolved(&self, max_depth: u32) -> String {
+        self.unparse_resolved_impl(max_depth, &mut HashSet::new())
+    }
+
+    fn unparse_resolved_impl(&self, depth: u32, expanding: &mut HashSet<ObjGen>) -> String {
+        let og = (self.get_id(), self.get_generation());
+        if self.is_indirect() {
+            if depth == 0 {
+                return format!("{} {} R", og.0, og.1);
+            }
+            if !expanding.insert(og) {
+                return "... (cycle)".to_owned();
+            }
+        }
+        let depth = if self.is_indirect() { depth - 1 } else { depth };
+
+        let result = match self.get_type() {
+            QPdfObjectType::Array => {
+                let items = QPdfArray::from(self.clone())
+                    .iter()
+                    .map(|item| item.unparse_resolved_impl(depth, expanding) + " ")
+                    .collect::<String>();
+                format!("[ {}]", items)
+            }
+            QPdfObjectType::Dictionary => dict_unparse_resolved(&self.clone().into(), depth, expanding),
+            QPdfObjectType::Stream => format!(
+                "{} stream",
+                dict_unparse_resolved(&QPdfStream::from(self.clone()).get_dictionary(), depth, expanding)
+            ),
+            _ => self.unparse_value(),
+        };
+
+        if self.is_indirect() {
+            expanding.remove(&og);
+        }
+        result
+    }
+
+    fn unparse_value(&self) -> String {
+        unsafe {
+            CStr::from_ptr(qpdf_sys::qpdf_oh_unparse_resolved(self.owner.inner(), self.inner))
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+}
+
+type ObjGen = (u32, u32);
+
+fn dict_unparse_resolved(dict: &QPdfDictionary, depth: u32, expanding: &mut HashSet<ObjGen>) -> String {
+    let entries = dict
+        .keys()
+        .into_iter()
+        .filter_map(|key| {
+            let value = dict.get(&key)?;
+            Some(format!("{} {} ", key, value.unparse_resolved_impl(depth, expanding)))
+        })
+        .collect::<String>();
+    format!("<< {}>>", entries)
+}
+
+fn dict_structural_eq(dict: &QPdfDictionary, other: &QPdfDictionary, visited: &mut HashSet<(ObjGen, ObjGen)>) -> bool {
+    let values = |dict: &QPdfDictionary| {
+        dict.keys()
+            .into_iter()
+            .filter_map(|key| dict.get(&key).map(|value| (key, value)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (values, other_values) = (values(dict), values(other));
+
+    values.len() == other_values.len()
+        && values.iter().all(|(key, value)| {
+            other_values
+                .get(key)
+                .map(|other_value| value.structural_eq_impl(other_value, visited))
+                .unwrap_or(false)
+        })
 }
 
 impl QPdfObjectLike for QPdfObject {
@@ -154,10 +510,54 @@ impl QPdfObjectLike for QPdfObject {
         unsafe { qpdf_sys::qpdf_oh_is_indirect(self.owner.inner(), self.inner) != 0 }
     }
 
+    fn is_null(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_null(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_bool(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_bool(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_integer(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_integer(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_name(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_name(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_string(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_string(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_array(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_array(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_dictionary(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_dictionary(self.owner.inner(), self.inner) != 0 }
+    }
+
+    fn is_stream(&self) -> bool {
+        unsafe { qpdf_sys::qpdf_oh_is_stream(self.owner.inner(), self.inner) != 0 }
+    }
+
     fn as_bool(&self) -> bool {
         unsafe { qpdf_sys::qpdf_oh_get_bool_value(self.owner.inner(), self.inner) != 0 }
     }
 
+    fn as_number(&self) -> Option<f64> {
+        match self.get_type() {
+            QPdfObjectType::Integer => {
+                Some(unsafe { qpdf_sys::qpdf_oh_get_int_value(self.owner.inner(), self.inner) } as f64)
+            }
+            QPdfObjectType::Real => {
+                Some(unsafe { qpdf_sys::qpdf_oh_get_numeric_value(self.owner.inner(), self.inner) })
+            }
+            _ => None,
+        }
+    }
+
     fn as_name(&self) -> String {
         unsafe {
             CStr::from_ptr(qpdf_sys::qpdf_oh_get_name(self.owner.inner(), self.inner))
@@ -190,6 +590,14 @@ impl QPdfObjectLike for QPdfObject {
         unsafe { qpdf_sys::qpdf_oh_get_generation(self.owner.inner(), self.inner) as _ }
     }
 
+    fn make_direct(&self) -> Result<QPdfObject> {
+        let obj = self.clone();
+        unsafe {
+            qpdf_sys::qpdf_oh_make_direct(self.owner.inner(), obj.inner);
+        }
+        self.owner.last_error_or_then(|| obj)
+    }
+
     /// convert to indirect object
     fn into_indirect(self) -> QPdfObject {
         unsafe {
@@ -223,15 +631,25 @@ impl Clone for QPdfObject {
     }
 }
 
+/// Indirect objects are equal if they refer to the same object of the same document,
+/// direct objects are equal if they are the same object handle.
 impl PartialEq for QPdfObject {
     fn eq(&self, other: &Self) -> bool {
-        self.inner == other.inner
+        self.identity() == other.identity()
     }
 }
 
+impl Eq for QPdfObject {}
+
 impl PartialOrd for QPdfObject {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.inner.partial_cmp(&other.inner)
+        self.identity().partial_cmp(&other.identity())
+    }
+}
+
+impl Hash for QPdfObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
     }
 }
 
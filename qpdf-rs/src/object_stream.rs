@@ -0,0 +1,53 @@
+use std::collections::HashSet;
+
+use crate::{QPdf, QPdfObjectLike, QPdfStream, QPdfTokenType, QPdfTokenizer, Result, StreamDecodeLevel};
+
+/// Placement of an indirect object in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectStreamEntry {
+    /// Object id
+    pub id: u32,
+    /// Object generation
+    pub gen: u32,
+    /// True if the object is stored in an object stream
+    pub in_object_stream: bool,
+}
+
+impl QPdf {
+    /// Report for every object returned by `get_all_objects` whether it is stored in an object stream.
+    /// The membership is read from the `/Type /ObjStm` streams of the document: their decoded data starts
+    /// with `/N` pairs of object id and offset. Objects created after the document was read are reported
+    /// as not being in an object stream.
+    pub fn object_stream_info(&self) -> Result<Vec<ObjectStreamEntry>> {
+        let objects = self.get_all_objects();
+
+        let mut members = HashSet::new();
+        for obj in objects.iter().filter(|obj| obj.is_stream()) {
+            let stream = QPdfStream::from(obj.clone());
+            let dict = stream.get_dictionary();
+            if dict.get("/Type").map(|t| t.as_name()).as_deref() != Some("/ObjStm") {
+                continue;
+            }
+            let count = dict.get("/N").and_then(|n| n.as_number()).unwrap_or_default() as usize;
+            let data = stream.get_data(StreamDecodeLevel::Generalized)?;
+            // the header pairs are object id and offset, only the ids are needed
+            members.extend(
+                QPdfTokenizer::new(&data)
+                    .take(count * 2)
+                    .step_by(2)
+                    .filter(|token| token.token_type() == QPdfTokenType::Integer)
+                    .filter_map(|token| std::str::from_utf8(token.raw()).ok()?.parse::<u32>().ok()),
+            );
+        }
+
+        // objects in object streams always have generation 0
+        Ok(objects
+            .iter()
+            .map(|obj| ObjectStreamEntry {
+                id: obj.get_id(),
+                gen: obj.get_generation(),
+                in_object_stream: obj.get_generation() == 0 && members.contains(&obj.get_id()),
+            })
+            .collect())
+    }
+}
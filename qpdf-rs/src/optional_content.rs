@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    tokenizer::{tokens_to_bytes, QPdfToken, QPdfTokenType, QPdfTokenizer},
+    QPdf, QPdfArray, QPdfDictionary, QPdfObject, QPdfObjectLike, Result,
+};
+
+type ObjGen = (u32, u32);
+
+enum MarkedContent {
+    Keep,
+    Strip,
+}
+
+impl QPdf {
+    /// Flatten optional content: the page content which belongs to hidden optional content groups is removed,
+    /// the optional content marks of the visible groups are stripped and `/OCProperties` is deleted from the catalog.
+    /// Only the default visibility state from the `/OCProperties /D` configuration is baked,
+    /// alternate configurations and usage-based visibility are ignored.
+    pub fn flatten_optional_content(&self) -> Result<()> {
+        let root = self.get_root_or_error()?;
+        let properties = match root.get("/OCProperties") {
+            Some(properties) if properties.is_dictionary() => QPdfDictionary::from(properties),
+            _ => return Ok(()),
+        };
+
+        let hidden = hidden_groups(&properties);
+
+        for page in self.get_pages()? {
+            let data = page.get_page_content_data()?;
+            let hidden_names = hidden_property_names(&page, &hidden);
+            let tokens = strip_optional_content(QPdfTokenizer::new(&data), &hidden_names);
+            page.set("/Contents", self.new_stream(tokens_to_bytes(&tokens)));
+        }
+
+        root.remove("/OCProperties");
+
+        Ok(())
+    }
+}
+
+fn object_refs(obj: Option<QPdfObject>) -> Vec<ObjGen> {
+    match obj {
+        Some(obj) if obj.is_array() => QPdfArray::from(obj)
+            .iter()
+            .filter(|item| item.is_indirect())
+            .map(|item| (item.get_id(), item.get_generation()))
+            .collect(),
+        Some(obj) if obj.is_indirect() => vec![(obj.get_id(), obj.get_generation())],
+        _ => Vec::new(),
+    }
+}
+
+fn hidden_groups(properties: &QPdfDictionary) -> HashSet<ObjGen> {
+    let config = match properties.get("/D") {
+        Some(config) if config.is_dictionary() => QPdfDictionary::from(config),
+        _ => return HashSet::new(),
+    };
+
+    if config.get("/BaseState").map(|s| s.as_name()).as_deref() == Some("/OFF") {
+        let visible = object_refs(config.get("/ON")).into_iter().collect::<HashSet<_>>();
+        object_refs(properties.get("/OCGs"))
+            .into_iter()
+            .filter(|og| !visible.contains(og))
+            .collect()
+    } else {
+        object_refs(config.get("/OFF")).into_iter().collect()
+    }
+}
+
+fn is_hidden(group: &QPdfObject, hidden: &HashSet<ObjGen>) -> bool {
+    if !group.is_dictionary() {
+        return false;
+    }
+    let dict = QPdfDictionary::from(group.clone());
+    if dict.get("/Type").map(|t| t.as_name()).as_deref() == Some("/OCMD") {
+        let states = object_refs(dict.get("/OCGs"))
+            .into_iter()
+            .map(|og| !hidden.contains(&og))
+            .collect::<Vec<_>>();
+        if states.is_empty() {
+            return false;
+        }
+        let policy = dict.get("/P").map(|p| p.as_name()).unwrap_or_default();
+        let visible = match policy.as_str() {
+            "/AllOn" => states.iter().all(|on| *on),
+            "/AnyOff" => states.iter().any(|on| !on),
+            "/AllOff" => states.iter().all(|on| !on),
+            _ => states.iter().any(|on| *on),
+        };
+        !visible
+    } else {
+        group.is_indirect() && hidden.contains(&(group.get_id(), group.get_generation()))
+    }
+}
+
+fn hidden_property_names(page: &QPdfDictionary, hidden: &HashSet<ObjGen>) -> HashMap<Vec<u8>, bool> {
+    let properties = page
+        .get_inherited("/Resources")
+        .filter(|resources| resources.is_dictionary())
+        .and_then(|resources| QPdfDictionary::from(resources).get("/Properties"))
+        .filter(|properties| properties.is_dictionary())
+        .map(QPdfDictionary::from);
+
+    properties
+        .map(|properties| {
+            properties
+                .keys()
+                .into_iter()
+                .filter_map(|key| {
+                    let group = properties.get(&key)?;
+                    Some((key.into_bytes(), is_hidden(&group, hidden)))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn strip_optional_content<I>(tokens: I, hidden_names: &HashMap<Vec<u8>, bool>) -> Vec<QPdfToken>
+where
+    I: Iterator<Item = QPdfToken>,
+{
+    let mut result = Vec::new();
+    let mut operands = Vec::new();
+    let mut marks = Vec::new();
+    let mut hidden_depth = 0;
+
+    for token in tokens {
+        if token.token_type() != QPdfTokenType::Operator {
+            operands.push(token);
+            continue;
+        }
+
+        let operator = token.raw();
+        if hidden_depth > 0 {
+            match operator {
+                b"BMC" | b"BDC" => hidden_depth += 1,
+                b"EMC" => hidden_depth -= 1,
+                _ => {}
+            }
+            operands.clear();
+            continue;
+        }
+
+        match operator {
+            b"BDC" if operands.len() == 2 && operands[0].raw() == b"/OC" => {
+                match hidden_names.get(operands[1].raw()) {
+                    Some(true) => hidden_depth = 1,
+                    _ => marks.push(MarkedContent::Strip),
+                }
+                operands.clear();
+            }
+            b"BMC" | b"BDC" => {
+                marks.push(MarkedContent::Keep);
+                result.append(&mut operands);
+                result.push(token);
+            }
+            b"EMC" => {
+                if let Some(MarkedContent::Strip) = marks.pop() {
+                    operands.clear();
+                } else {
+                    result.append(&mut operands);
+                    result.push(token);
+                }
+            }
+            _ => {
+                result.append(&mut operands);
+                result.push(token);
+            }
+        }
+    }
+    result.append(&mut operands);
+    result
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike, Result};
+
+/// Fit mode of the destination created by `QPdf::set_open_action_goto`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fit {
+    /// Fit the whole page into the window (`/Fit`)
+    FitPage,
+    /// Fit the page width into the window, starting at the top of the page (`/FitH`)
+    FitWidth,
+    /// Position the point `x`, `y` at the top left corner of the window with the `zoom` factor (`/XYZ`),
+    /// zero zoom keeps the current zoom
+    XYZ { x: f64, y: f64, zoom: f64 },
+}
+
+/// Outline item (bookmark) of the document
+pub struct QPdfOutline {
+    dictionary: QPdfDictionary,
+}
+
+impl QPdfOutline {
+    /// Get the underlying outline item dictionary
+    pub fn dictionary(&self) -> &QPdfDictionary {
+        &self.dictionary
+    }
+
+    /// Get the bookmark title
+    pub fn title(&self) -> String {
+        self.dictionary
+            .get("/Title")
+            .map(|title| title.as_string())
+            .unwrap_or_default()
+    }
+
+    /// Get the `/Dest` destination or the `/A` action of the item, see `QPdf::resolve_destination`
+    pub fn destination(&self) -> Option<QPdfObject> {
+        self.dictionary.get("/Dest").or_else(|| self.dictionary.get("/A"))
+    }
+
+    /// Get the child items in order
+    pub fn children(&self) -> Vec<QPdfOutline> {
+        outline_children(&self.dictionary)
+    }
+}
+
+// Follow the /First and /Next links of the outline node, stopping at a loop
+fn outline_children(node: &QPdfDictionary) -> Vec<QPdfOutline> {
+    let mut result = Vec::new();
+    let mut visited = HashSet::new();
+    let mut next = node.get("/First");
+
+    while let Some(item) = next.filter(|item| item.is_dictionary()) {
+        if item.is_indirect() && !visited.insert((item.get_id(), item.get_generation())) {
+            break;
+        }
+        let dictionary = QPdfDictionary::from(item);
+        next = dictionary.get("/Next");
+        result.push(QPdfOutline { dictionary });
+    }
+    result
+}
+
+/// Destination resolved to a page of the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destination {
+    /// Zero-based index of the target page
+    pub page_index: u32,
+    /// Fit type, for example `/XYZ` or `/Fit`
+    pub fit: String,
+    /// Fit parameters, for `/XYZ` these are left, top and zoom. `None` means the value is unchanged.
+    pub params: Vec<Option<f64>>,
+}
+
+impl QPdf {
+    /// Get the top level outline items, use `QPdfOutline::children` to walk the tree
+    pub fn get_outlines(&self) -> Vec<QPdfOutline> {
+        self.get_root()
+            .and_then(|root| root.get("/Outlines"))
+            .filter(|outlines| outlines.is_dictionary())
+            .map(|outlines| outline_children(&outlines.into()))
+            .unwrap_or_default()
+    }
+
+    /// Add a bookmark pointing to the top of the page `page_index` as the last child of `parent`,
+    /// or as the last top level item if `parent` is `None`. The outline root is created if needed.
+    /// New items are open, the `/Count` entries of the ancestors are updated accordingly.
+    pub fn add_outline(&self, title: &str, page_index: u32, parent: Option<&QPdfOutline>) -> Result<QPdfOutline> {
+        let page = self
+            .get_page(page_index)
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::PagesError, format!("Page {} does not exist", page_index)))?;
+
+        let parent = match parent {
+            Some(parent) => QPdfDictionary::from(parent.dictionary.as_object().clone()),
+            None => self.get_or_create_outline_root()?,
+        };
+
+        let [left, _, _, top] = self.get_page_boxes(&page).effective_crop_box().unwrap_or_default();
+        let dest = self.new_array_from([
+            page.into(),
+            self.new_name("/XYZ"),
+            self.new_number(left),
+            self.new_number(top),
+            self.new_null(),
+        ]);
+        let item: QPdfDictionary = self
+            .new_dictionary_from([
+                ("/Title", self.new_utf8_string(title)),
+                ("/Parent", parent.as_object().clone()),
+                ("/Dest", dest.into()),
+            ])
+            .into_indirect()
+            .into();
+
+        match parent.get("/Last").filter(|last| last.is_dictionary()) {
+            Some(last) => {
+                item.set("/Prev", &last);
+                QPdfDictionary::from(last).set("/Next", &item);
+            }
+            None => parent.set("/First", &item),
+        }
+        parent.set("/Last", &item);
+
+        // the item is visible in all open ancestors up to the first closed one, which has a negative count
+        let mut visited = HashSet::new();
+        let mut node = Some(parent);
+        while let Some(current) = node {
+            if !visited.insert((current.get_id(), current.get_generation())) {
+                break;
+            }
+            let count = current.get("/Count").and_then(|count| count.as_number()).unwrap_or(0.0) as i64;
+            if count < 0 {
+                current.set("/Count", self.new_integer(count - 1));
+                break;
+            }
+            current.set("/Count", self.new_integer(count + 1));
+            node = current
+                .get("/Parent")
+                .filter(|parent| parent.is_dictionary())
+                .map(QPdfDictionary::from);
+        }
+
+        Ok(QPdfOutline { dictionary: item })
+    }
+
+    /// Get the catalog `/OpenAction`, which is either a destination array or an action dictionary
+    pub fn get_open_action(&self) -> Option<QPdfObject> {
+        self.get_root()?.get("/OpenAction")
+    }
+
+    /// Set the catalog `/OpenAction` to a `/GoTo` action which displays the page `page_index`
+    /// with the given fit mode when the document is opened
+    pub fn set_open_action_goto(&self, page_index: u32, fit: Fit) -> Result<()> {
+        let page = self
+            .get_page(page_index)
+            .ok_or_else(|| QPdfError::new(QPdfErrorCode::PagesError, format!("Page {} does not exist", page_index)))?;
+        let top = self
+            .get_page_boxes(&page)
+            .effective_crop_box()
+            .map(|rect| rect[3])
+            .unwrap_or_default();
+
+        let dest = self.new_array_from([page.into()]);
+        match fit {
+            Fit::FitPage => dest.push(self.new_name("/Fit")),
+            Fit::FitWidth => {
+                dest.push(self.new_name("/FitH"));
+                dest.push(self.new_number(top));
+            }
+            Fit::XYZ { x, y, zoom } => {
+                dest.push(self.new_name("/XYZ"));
+                dest.push(self.new_number(x));
+                dest.push(self.new_number(y));
+                dest.push(self.new_number(zoom));
+            }
+        }
+
+        let action = self.new_dictionary_from([("/S", self.new_name("/GoTo")), ("/D", dest.into())]);
+        self.get_root_or_error()?.set("/OpenAction", action);
+        Ok(())
+    }
+
+    /// Open the document at the page `index` fitted into the window
+    pub fn open_at_page(&self, index: u32) -> Result<()> {
+        self.set_open_action_goto(index, Fit::FitPage)
+    }
+
+    /// Open the document at the first page with its width fitted into the window
+    pub fn open_fit_width(&self) -> Result<()> {
+        self.set_open_action_goto(0, Fit::FitWidth)
+    }
+
+    fn get_or_create_outline_root(&self) -> Result<QPdfDictionary> {
+        let root = self.get_root_or_error()?;
+        if let Some(outlines) = root.get("/Outlines").filter(|outlines| outlines.is_dictionary()) {
+            return Ok(outlines.into());
+        }
+        let outlines = self
+            .new_dictionary_from([("/Type", self.new_name("/Outlines"))])
+            .into_indirect();
+        root.set("/Outlines", &outlines);
+        Ok(outlines.into())
+    }
+
+    /// Resolve the destination to a page index and the fit parameters.
+    ///
+    /// The destination may be an explicit array like `[page /XYZ x y z]`, a string looked up in the
+    /// `/Names /Dests` name tree, a name looked up in the catalog `/Dests` dictionary, or a dictionary
+    /// with the `/D` entry such as a `/GoTo` action. Returns `None` if the destination can not be resolved
+    /// to a page of this document.
+    pub fn resolve_destination(&self, dest: &QPdfObject) -> Option<Destination> {
+        self.resolve_destination_impl(dest, 0)
+    }
+
+    fn resolve_destination_impl(&self, dest: &QPdfObject, depth: u32) -> Option<Destination> {
+        // named destinations may refer to each other, the depth guards against loops
+        if depth > 8 {
+            return None;
+        }
+
+        if dest.is_array() {
+            return self.explicit_destination(&QPdfArray::from(dest.clone()));
+        }
+
+        let target = if dest.is_string() {
+            self.get_name_tree("/Dests")?.get(&dest.as_string())?
+        } else if dest.is_name() {
+            let dests = self.get_root()?.get("/Dests").filter(|dests| dests.is_dictionary())?;
+            QPdfDictionary::from(dests).get(&dest.as_name())?
+        } else if dest.is_dictionary() {
+            QPdfDictionary::from(dest.clone()).get("/D")?
+        } else {
+            return None;
+        };
+
+        self.resolve_destination_impl(&target, depth + 1)
+    }
+
+    fn explicit_destination(&self, dest: &QPdfArray) -> Option<Destination> {
+        let page = dest.get(0)?;
+        let page_index = if page.is_dictionary() {
+            let index = self.get_pages().ok()?.iter().position(|p| p.as_object() == &page)?;
+            index as u32
+        } else if page.is_integer() {
+            // remote destinations use page numbers, accept them if the page exists
+            let index = page.as_number()? as u32;
+            (index < self.get_num_pages().ok()?).then_some(index)?
+        } else {
+            return None;
+        };
+
+        let fit = dest.get(1).filter(|fit| fit.is_name())?.as_name();
+        let params = dest.iter().skip(2).map(|param| param.as_number()).collect();
+
+        Some(Destination {
+            page_index,
+            fit,
+            params,
+        })
+    }
+}
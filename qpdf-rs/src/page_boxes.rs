@@ -0,0 +1,110 @@
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfError, QPdfErrorCode, QPdfObject, QPdfObjectLike, Result};
+
+/// Page boxes in the `[llx lly urx ury]` form, `None` if the box is not defined for the page
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PageBoxes {
+    pub media_box: Option<[f64; 4]>,
+    pub crop_box: Option<[f64; 4]>,
+    pub bleed_box: Option<[f64; 4]>,
+    pub trim_box: Option<[f64; 4]>,
+    pub art_box: Option<[f64; 4]>,
+}
+
+impl PageBoxes {
+    /// Return the crop box, which defaults to the media box when absent
+    pub fn effective_crop_box(&self) -> Option<[f64; 4]> {
+        self.crop_box.or(self.media_box)
+    }
+}
+
+/// What to do when a box being set is not within the page media box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoxBoundsPolicy {
+    /// Clamp the box to the media box
+    Clamp,
+    /// Fail with `QPdfErrorCode::InvalidParameter`
+    Error,
+}
+
+impl QPdf {
+    /// Get page boxes of the page. `/MediaBox` and `/CropBox` are inherited from the page tree,
+    /// the other boxes are taken from the page itself.
+    pub fn get_page_boxes(&self, page: &QPdfDictionary) -> PageBoxes {
+        PageBoxes {
+            media_box: rect_from_object(page.get_inherited("/MediaBox")),
+            crop_box: rect_from_object(page.get_inherited("/CropBox")),
+            bleed_box: rect_from_object(page.get("/BleedBox")),
+            trim_box: rect_from_object(page.get("/TrimBox")),
+            art_box: rect_from_object(page.get("/ArtBox")),
+        }
+    }
+
+    /// Set the crop box of the page, for example to trim the print margins.
+    /// The rectangle is normalized so that the lower left corner comes first and checked against
+    /// the page media box, which may be inherited. A box which is still empty after clamping is an error.
+    pub fn set_crop_box(&self, page: &QPdfDictionary, rect: [f64; 4], policy: BoxBoundsPolicy) -> Result<()> {
+        let media_box = rect_from_object(page.get_inherited("/MediaBox"))
+            .ok_or_else(|| invalid_box("Page has no valid /MediaBox".to_owned()))?;
+        let (media_box, rect) = (normalize(media_box), normalize(rect));
+
+        let inside =
+            rect[0] >= media_box[0] && rect[1] >= media_box[1] && rect[2] <= media_box[2] && rect[3] <= media_box[3];
+
+        let crop_box = if inside {
+            rect
+        } else if policy == BoxBoundsPolicy::Clamp {
+            [
+                rect[0].max(media_box[0]),
+                rect[1].max(media_box[1]),
+                rect[2].min(media_box[2]),
+                rect[3].min(media_box[3]),
+            ]
+        } else {
+            return Err(invalid_box(format!(
+                "Crop box {:?} is outside of the media box {:?}",
+                rect, media_box
+            )));
+        };
+
+        if crop_box[0] >= crop_box[2] || crop_box[1] >= crop_box[3] {
+            return Err(invalid_box(format!(
+                "Crop box {:?} does not intersect the media box {:?}",
+                rect, media_box
+            )));
+        }
+
+        page.set_rect("/CropBox", crop_box);
+        Ok(())
+    }
+
+    /// Set the crop box of all pages, see `set_crop_box` for details.
+    /// Pages processed before a failing one keep their new crop box.
+    pub fn crop_all_pages(&self, rect: [f64; 4], policy: BoxBoundsPolicy) -> Result<()> {
+        for page in self.get_pages()? {
+            self.set_crop_box(&page, rect, policy)?;
+        }
+        Ok(())
+    }
+}
+
+fn normalize(rect: [f64; 4]) -> [f64; 4] {
+    [
+        rect[0].min(rect[2]),
+        rect[1].min(rect[3]),
+        rect[0].max(rect[2]),
+        rect[1].max(rect[3]),
+    ]
+}
+
+fn invalid_box(description: String) -> QPdfError {
+    QPdfError::new(QPdfErrorCode::InvalidParameter, description)
+}
+
+// Convert an array of 4 numbers into a rectangle
+pub(crate) fn rect_from_object(obj: Option<QPdfObject>) -> Option<[f64; 4]> {
+    let obj = obj?;
+    if !obj.is_array() {
+        return None;
+    }
+    QPdfArray::from(obj).as_f64_vec()?.try_into().ok()
+}
@@ -0,0 +1,75 @@
+use std::{path::Path, rc::Rc};
+
+use crate::{QPdf, Result};
+
+/// Options which are applied to the document before it is read
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadOptions {
+    password: Option<String>,
+    attempt_recovery: Option<bool>,
+    ignore_xref_streams: Option<bool>,
+}
+
+impl ReadOptions {
+    /// Create default read options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the password for the encrypted document
+    pub fn password(&mut self, password: &str) -> &mut Self {
+        self.password = Some(password.to_owned());
+        self
+    }
+
+    /// Enable or disable the recovery of damaged files
+    pub fn attempt_recovery(&mut self, flag: bool) -> &mut Self {
+        self.attempt_recovery = Some(flag);
+        self
+    }
+
+    /// Enable or disable ignoring of the cross-reference streams
+    pub fn ignore_xref_streams(&mut self, flag: bool) -> &mut Self {
+        self.ignore_xref_streams = Some(flag);
+        self
+    }
+
+    /// Read PDF from the file with these options, see `QPdf::read_with_options`
+    pub fn read_file<P: AsRef<Path>>(&self, path: P) -> Result<QPdf> {
+        QPdf::read_with_options(path, self)
+    }
+
+    /// Read PDF from memory with these options. The buffer is copied.
+    pub fn read_memory<T: AsRef<[u8]>>(&self, buffer: T) -> Result<QPdf> {
+        let qpdf = QPdf::new();
+        self.apply(&qpdf);
+        qpdf.read_owned_buffer(Rc::new(buffer.as_ref().to_vec()), self.password.as_deref())
+    }
+
+    // Apply the flags to the document which is not read yet
+    fn apply(&self, qpdf: &QPdf) {
+        if let Some(attempt_recovery) = self.attempt_recovery {
+            qpdf.enable_recovery(attempt_recovery);
+        }
+        if let Some(ignore_xref_streams) = self.ignore_xref_streams {
+            qpdf.ignore_xref_streams(ignore_xref_streams);
+        }
+    }
+}
+
+impl QPdf {
+    /// Start configuring the document read, the options are applied before parsing:
+    /// `QPdf::builder().attempt_recovery(true).ignore_xref_streams(true).read_file(path)`.
+    /// The `read` functions remain the shortcuts for the default options.
+    pub fn builder() -> ReadOptions {
+        ReadOptions::new()
+    }
+
+    /// Read PDF from the file with the given options, which are applied before parsing
+    pub fn read_with_options<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<QPdf> {
+        let qpdf = QPdf::new();
+        options.apply(&qpdf);
+        qpdf.do_read_file(path.as_ref(), options.password.as_deref())?;
+        Ok(qpdf)
+    }
+}
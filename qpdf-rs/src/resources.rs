@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::{
+    tokenizer::{QPdfTokenType, QPdfTokenizer},
+    QPdf, QPdfDictionary, QPdfObjectLike, Result,
+};
+
+// Resource categories which are referenced by name from the content stream
+const RESOURCE_CATEGORIES: [&str; 7] = [
+    "/ExtGState",
+    "/ColorSpace",
+    "/Pattern",
+    "/Shading",
+    "/XObject",
+    "/Font",
+    "/Properties",
+];
+
+impl QPdf {
+    /// Remove the page resources which are not referenced from the page contents.
+    ///
+    /// Every name in the content stream is treated as a possible reference, the entries of the
+    /// resource categories (`/Font`, `/XObject`, `/ExtGState` etc.) which do not appear there are removed.
+    /// Each page gets its own copy of the resource dictionaries, so resources shared between pages
+    /// are only removed from the pages which do not use them.
+    /// The resources of form XObjects and annotation appearances are not changed.
+    pub fn remove_unused_resources(&self) -> Result<()> {
+        self.push_inherited_attributes();
+
+        for page in self.get_pages()? {
+            let resources = match page.get("/Resources") {
+                Some(resources) if resources.is_dictionary() => QPdfDictionary::from(resources),
+                _ => continue,
+            };
+
+            let data = page.get_page_content_data()?;
+            let used_names = QPdfTokenizer::new(&data)
+                .filter(|token| token.token_type() == QPdfTokenType::Name)
+                .map(|token| decode_name(token.raw()))
+                .collect::<HashSet<_>>();
+
+            let new_resources = self.new_dictionary();
+            for key in resources.keys() {
+                let value = match resources.get(&key) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                if !RESOURCE_CATEGORIES.contains(&key.as_str()) || !value.is_dictionary() {
+                    new_resources.set(&key, &value);
+                    continue;
+                }
+
+                let category = QPdfDictionary::from(value);
+                let new_category = self.new_dictionary();
+                for name in category.keys() {
+                    if let Some(resource) = category.get(&name).filter(|_| used_names.contains(&name)) {
+                        new_category.set(&name, resource);
+                    }
+                }
+                if !new_category.keys().is_empty() {
+                    new_resources.set(&key, new_category);
+                }
+            }
+            page.set("/Resources", new_resources);
+        }
+
+        Ok(())
+    }
+}
+
+// Decode `#xx` escapes in the raw name token
+fn decode_name(raw: &[u8]) -> String {
+    let mut result = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let hex = raw
+            .get(i + 1..i + 3)
+            .filter(|_| raw[i] == b'#')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(ch) => {
+                result.push(ch);
+                i += 3;
+            }
+            None => {
+                result.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
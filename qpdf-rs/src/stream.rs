@@ -1,6 +1,6 @@
 use std::{fmt, ops::Deref, ptr, slice};
 
-use crate::{QPdfDictionary, QPdfObject, QPdfObjectLike, Result};
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfObject, QPdfObjectLike, Result};
 
 /// Stream decoding level
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
@@ -58,6 +58,83 @@ impl StreamDataMode {
     }
 }
 
+/// Stream filter which the stream data is encoded with
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
+pub enum StreamFilter {
+    None,
+    Flate,
+    Lzw,
+    Ascii85,
+    AsciiHex,
+    RunLength,
+    CcittFax,
+    Jbig2,
+    Dct,
+    Jpx,
+}
+
+impl StreamFilter {
+    /// Return filter name or `None` if the data is not encoded
+    pub fn as_name(&self) -> Option<&'static str> {
+        match self {
+            StreamFilter::None => None,
+            StreamFilter::Flate => Some("/FlateDecode"),
+            StreamFilter::Lzw => Some("/LZWDecode"),
+            StreamFilter::Ascii85 => Some("/ASCII85Decode"),
+            StreamFilter::AsciiHex => Some("/ASCIIHexDecode"),
+            StreamFilter::RunLength => Some("/RunLengthDecode"),
+            StreamFilter::CcittFax => Some("/CCITTFaxDecode"),
+            StreamFilter::Jbig2 => Some("/JBIG2Decode"),
+            StreamFilter::Dct => Some("/DCTDecode"),
+            StreamFilter::Jpx => Some("/JPXDecode"),
+        }
+    }
+
+    /// Return filter for the specified filter name
+    pub fn from_name(name: &str) -> Option<StreamFilter> {
+        match name {
+            "/FlateDecode" => Some(StreamFilter::Flate),
+            "/LZWDecode" => Some(StreamFilter::Lzw),
+            "/ASCII85Decode" => Some(StreamFilter::Ascii85),
+            "/ASCIIHexDecode" => Some(StreamFilter::AsciiHex),
+            "/RunLengthDecode" => Some(StreamFilter::RunLength),
+            "/CCITTFaxDecode" => Some(StreamFilter::CcittFax),
+            "/JBIG2Decode" => Some(StreamFilter::Jbig2),
+            "/DCTDecode" => Some(StreamFilter::Dct),
+            "/JPXDecode" => Some(StreamFilter::Jpx),
+            _ => None,
+        }
+    }
+
+    /// Return true for the general-purpose filters which QPDF decodes with `StreamDecodeLevel::Generalized`
+    pub fn is_generalized(&self) -> bool {
+        matches!(
+            self,
+            StreamFilter::Flate | StreamFilter::Lzw | StreamFilter::Ascii85 | StreamFilter::AsciiHex
+        )
+    }
+}
+
+pub(crate) struct StreamDataProvider {
+    stream: qpdf_sys::qpdf_oh,
+    params: qpdf_sys::qpdf_oh,
+    filter: StreamFilter,
+    provider: Box<dyn FnMut() -> Vec<u8>>,
+}
+
+impl QPdf {
+    // Call the registered stream data providers and replace the stream data with their output
+    pub(crate) fn apply_stream_providers(&self) -> Result<()> {
+        let providers = self.inner.providers.take();
+        for mut provider in providers {
+            let stream = QPdfStream::from(QPdfObject::new(self.clone(), provider.stream));
+            let params = QPdfObject::new(self.clone(), provider.params);
+            stream.try_replace_data((provider.provider)(), provider.filter, params)?;
+        }
+        Ok(())
+    }
+}
+
 /// QPdfStream represents a stream object
 pub struct QPdfStream {
     inner: QPdfObject,
@@ -87,6 +164,64 @@ impl QPdfStream {
         }
     }
 
+    /// Replace stream data and return the error reported by QPDF. The data must be already encoded
+    /// with the specified filter, `params` is the `/DecodeParms` value or a null object.
+    /// Use `replace_data` with an array of filter names for a filter chain.
+    pub fn try_replace_data<P, D>(&self, data: D, filter: StreamFilter, params: P) -> Result<()>
+    where
+        P: AsRef<QPdfObject>,
+        D: AsRef<[u8]>,
+    {
+        let owner = &self.inner.owner;
+        let filter = match filter.as_name() {
+            Some(name) => owner.new_name(name),
+            None => owner.new_null(),
+        };
+        owner.wrap_ffi_call(|| self.replace_data(data, filter, params))
+    }
+
+    /// Defer the replacement of stream data until the document is written. The provider function is called once,
+    /// by the first write, and its output replaces the stream data as with `try_replace_data`; the stream data
+    /// is not changed before that. This is not a streaming provider: the C API only accepts complete buffers,
+    /// so the whole output of the provider is held in memory at write time and stays in the document afterwards.
+    /// The data must be encoded with the specified filter, `params` is the `/DecodeParms` value or a null object.
+    pub fn replace_data_on_write<F, P>(&self, provider: F, filter: StreamFilter, params: P)
+    where
+        F: FnMut() -> Vec<u8> + 'static,
+        P: AsRef<QPdfObject>,
+    {
+        let owner = &self.inner.owner;
+        // raw handles are kept instead of objects to avoid a reference cycle with the owner
+        unsafe {
+            owner.inner.providers.borrow_mut().push(StreamDataProvider {
+                stream: qpdf_sys::qpdf_oh_new_object(owner.inner(), self.inner.inner),
+                params: qpdf_sys::qpdf_oh_new_object(owner.inner(), params.as_ref().inner),
+                filter,
+                provider: Box::new(provider),
+            });
+        }
+    }
+
+    /// Return filters applied to the stream data, in the decoding order.
+    /// An unknown filter name makes the result `None`.
+    pub fn get_filters(&self) -> Option<Vec<StreamFilter>> {
+        match self.get_dictionary().get("/Filter") {
+            None => Some(Vec::new()),
+            Some(filter) if filter.is_name() => StreamFilter::from_name(&filter.as_name()).map(|filter| vec![filter]),
+            Some(filter) if filter.is_array() => QPdfArray::from(filter)
+                .iter()
+                .map(|filter| {
+                    if filter.is_name() {
+                        StreamFilter::from_name(&filter.as_name())
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            Some(_) => None,
+        }
+    }
+
     /// Get stream data
     pub fn get_data(&self, decode_level: StreamDecodeLevel) -> Result<QPdfStreamData> {
         unsafe {
@@ -107,6 +242,13 @@ impl QPdfStream {
         }
     }
 
+    /// Get stream data as it is stored in the file, with all filters such as `/FlateDecode` or `/DCTDecode` still applied.
+    /// This is the same as `get_data(StreamDecodeLevel::None)`, which also never decodes,
+    /// but states the intent explicitly. Note that QPDF always decrypts the data of encrypted documents.
+    pub fn get_raw_data(&self) -> Result<QPdfStreamData> {
+        self.get_data(StreamDecodeLevel::None)
+    }
+
     /// Return a dictionary associated with the stream
     pub fn get_dictionary(&self) -> QPdfDictionary {
         unsafe {
@@ -169,6 +311,18 @@ impl QPdfStreamData {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Consume the data and return it as a vector. The buffer is allocated by QPDF,
+    /// so the data is copied into the vector and the buffer is freed.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+}
+
+impl From<QPdfStreamData> for Vec<u8> {
+    fn from(data: QPdfStreamData) -> Self {
+        data.into_vec()
+    }
 }
 
 impl AsRef<[u8]> for QPdfStreamData {
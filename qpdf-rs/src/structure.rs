@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfObject, QPdfObjectLike};
+
+/// Element of the logical structure tree of a tagged PDF
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StructElement {
+    /// Structure type, for example `/P`, `/H1` or `/Figure`. For the tree root this is `/StructTreeRoot`.
+    pub tag: String,
+    /// Marked-content ids of the page content which belongs to this element
+    pub mcids: Vec<i64>,
+    /// Alternate description, usually present for figures
+    pub alt: Option<String>,
+    /// Child structure elements in order
+    pub children: Vec<StructElement>,
+}
+
+impl QPdf {
+    /// Get the logical structure tree from the catalog `/StructTreeRoot`.
+    ///
+    /// The `/K` entries are followed recursively, they may hold a marked-content id, a marked-content
+    /// reference (`/MCR`), an object reference (`/OBJR`, which is skipped), a child element or an array
+    /// of them. Elements which are reached again through a loop are skipped.
+    pub fn get_structure_tree(&self) -> Option<StructElement> {
+        let root = self
+            .get_root()?
+            .get("/StructTreeRoot")
+            .filter(|root| root.is_dictionary())?;
+        let mut visited = HashSet::new();
+        let mut element = StructElement {
+            tag: "/StructTreeRoot".to_owned(),
+            ..Default::default()
+        };
+        if let Some(kids) = QPdfDictionary::from(root).get("/K") {
+            read_kids(&kids, &mut element, &mut visited);
+        }
+        Some(element)
+    }
+}
+
+fn read_element(dict: QPdfDictionary, visited: &mut HashSet<(u32, u32)>) -> StructElement {
+    let mut element = StructElement {
+        tag: dict
+            .get("/S")
+            .filter(|tag| tag.is_name())
+            .map(|tag| tag.as_name())
+            .unwrap_or_default(),
+        alt: dict
+            .get("/Alt")
+            .filter(|alt| alt.is_string())
+            .map(|alt| alt.as_string()),
+        ..Default::default()
+    };
+    if let Some(kids) = dict.get("/K") {
+        read_kids(&kids, &mut element, visited);
+    }
+    element
+}
+
+fn read_kids(kids: &QPdfObject, element: &mut StructElement, visited: &mut HashSet<(u32, u32)>) {
+    if kids.is_integer() {
+        element.mcids.extend(kids.as_number().map(|mcid| mcid as i64));
+    } else if kids.is_array() {
+        for kid in QPdfArray::from(kids.clone()).iter() {
+            // /K arrays are not nested, skipping them also guards against loops of indirect arrays
+            if !kid.is_array() {
+                read_kids(&kid, element, visited);
+            }
+        }
+    } else if kids.is_dictionary() {
+        if kids.is_indirect() && !visited.insert((kids.get_id(), kids.get_generation())) {
+            return;
+        }
+        let dict = QPdfDictionary::from(kids.clone());
+        match dict.get("/Type").map(|t| t.as_name()).as_deref() {
+            Some("/MCR") => element.mcids.extend(
+                dict.get("/MCID")
+                    .and_then(|mcid| mcid.as_number())
+                    .map(|mcid| mcid as i64),
+            ),
+            Some("/OBJR") => {}
+            _ => element.children.push(read_element(dict, visited)),
+        }
+    }
+}
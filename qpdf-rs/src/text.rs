@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use crate::{
+    tokenizer::{QPdfToken, QPdfTokenType, QPdfTokenizer},
+    QPdf, QPdfDictionary, QPdfObjectLike, Result,
+};
+
+// Characters of WinAnsiEncoding in the range 0x80..=0x9F, the rest of the upper half is Latin-1
+const WIN_ANSI_80_9F: [char; 32] = [
+    '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž', '\u{fffd}', '\u{fffd}',
+    '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{fffd}', 'ž', 'Ÿ',
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum FontEncoding {
+    WinAnsi,
+    Latin1,
+    // composite fonts with multibyte codes which are not decoded
+    Unsupported,
+}
+
+impl QPdf {
+    /// Extract the text of the page in the content stream order.
+    ///
+    /// The string operands of the `Tj`, `TJ`, `'` and `"` operators are decoded using the font encoding:
+    /// `/WinAnsiEncoding` is supported and other simple font encodings are decoded as Latin-1.
+    /// The text of composite (`/Type0`) fonts is skipped and `/ToUnicode` maps and `/Differences` are ignored.
+    /// Line breaks are inserted for the line moving operators and between text objects, spaces for
+    /// the large negative offsets in `TJ` arrays. Text in form XObjects and annotations is not included.
+    pub fn get_page_text(&self, page: &QPdfDictionary) -> Result<String> {
+        let encodings = font_encodings(page);
+        let data = page.get_page_content_data()?;
+
+        let mut text = String::new();
+        let mut operands: Vec<QPdfToken> = Vec::new();
+        let mut encoding = FontEncoding::Latin1;
+
+        for token in QPdfTokenizer::new(&data) {
+            if token.token_type() != QPdfTokenType::Operator {
+                operands.push(token);
+                continue;
+            }
+
+            match token.raw() {
+                b"Tf" => {
+                    if let Some(name) = operands.iter().find(|token| token.token_type() == QPdfTokenType::Name) {
+                        let name = String::from_utf8_lossy(name.raw()).into_owned();
+                        encoding = encodings.get(&name).copied().unwrap_or(FontEncoding::Latin1);
+                    }
+                }
+                b"Td" | b"TD" => {
+                    let moves_down = operands
+                        .get(1)
+                        .and_then(|ty| std::str::from_utf8(ty.raw()).ok())
+                        .and_then(|ty| ty.parse::<f64>().ok())
+                        .map(|ty| ty != 0.0)
+                        .unwrap_or(false);
+                    if moves_down {
+                        new_line(&mut text);
+                    } else {
+                        space(&mut text);
+                    }
+                }
+                b"T*" | b"ET" => new_line(&mut text),
+                b"Tj" | b"'" | b"\"" => {
+                    if token.raw() != b"Tj" {
+                        new_line(&mut text);
+                    }
+                    if let Some(string) = operands.iter().rev().find(|t| t.token_type() == QPdfTokenType::String) {
+                        text.push_str(&decode_text(&decode_string(string.raw()), encoding));
+                    }
+                }
+                b"TJ" => {
+                    for operand in &operands {
+                        match operand.token_type() {
+                            QPdfTokenType::String => {
+                                text.push_str(&decode_text(&decode_string(operand.raw()), encoding))
+                            }
+                            QPdfTokenType::Integer | QPdfTokenType::Real => {
+                                let offset = std::str::from_utf8(operand.raw())
+                                    .ok()
+                                    .and_then(|offset| offset.parse::<f64>().ok())
+                                    .unwrap_or_default();
+                                // the offset is in thousandths of the text space unit, a large one is a word gap
+                                if offset < -200.0 {
+                                    space(&mut text);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+            operands.clear();
+        }
+
+        Ok(text.trim_end().to_owned())
+    }
+}
+
+fn new_line(text: &mut String) {
+    let trimmed_len = text.trim_end_matches(' ').len();
+    text.truncate(trimmed_len);
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+fn space(text: &mut String) {
+    if !text.is_empty() && !text.ends_with(' ') && !text.ends_with('\n') {
+        text.push(' ');
+    }
+}
+
+// Map the font resource names of the page to their encodings
+fn font_encodings(page: &QPdfDictionary) -> HashMap<String, FontEncoding> {
+    let fonts = page
+        .get_inherited("/Resources")
+        .filter(|resources| resources.is_dictionary())
+        .and_then(|resources| QPdfDictionary::from(resources).get("/Font"))
+        .filter(|fonts| fonts.is_dictionary())
+        .map(QPdfDictionary::from);
+
+    let fonts = match fonts {
+        Some(fonts) => fonts,
+        None => return HashMap::new(),
+    };
+
+    fonts
+        .keys()
+        .into_iter()
+        .filter_map(|name| {
+            let font = QPdfDictionary::from(fonts.get(&name).filter(|font| font.is_dictionary())?);
+            if font.get("/Subtype").map(|s| s.as_name()).as_deref() == Some("/Type0") {
+                return Some((name, FontEncoding::Unsupported));
+            }
+            let encoding = match font.get("/Encoding") {
+                Some(encoding) if encoding.is_name() => Some(encoding.as_name()),
+                Some(encoding) if encoding.is_dictionary() => QPdfDictionary::from(encoding)
+                    .get("/BaseEncoding")
+                    .filter(|base| base.is_name())
+                    .map(|base| base.as_name()),
+                _ => None,
+            };
+            let encoding = match encoding.as_deref() {
+                Some("/WinAnsiEncoding") => FontEncoding::WinAnsi,
+                _ => FontEncoding::Latin1,
+            };
+            Some((name, encoding))
+        })
+        .collect()
+}
+
+fn decode_text(data: &[u8], encoding: FontEncoding) -> String {
+    match encoding {
+        FontEncoding::Unsupported => String::new(),
+        FontEncoding::WinAnsi => data
+            .iter()
+            .map(|&ch| match ch {
+                0x80..=0x9f => WIN_ANSI_80_9F[(ch - 0x80) as usize],
+                _ => ch as char,
+            })
+            .collect(),
+        FontEncoding::Latin1 => data.iter().map(|&ch| ch as char).collect(),
+    }
+}
+
+// Decode the raw literal or hex string token into bytes
+pub(crate) fn decode_string(raw: &[u8]) -> Vec<u8> {
+    match raw.first() {
+        Some(b'<') => decode_hex_string(raw),
+        Some(b'(') => decode_literal_string(&raw[1..raw.len().saturating_sub(1).max(1)]),
+        _ => Vec::new(),
+    }
+}
+
+fn decode_hex_string(raw: &[u8]) -> Vec<u8> {
+    let mut digits = raw
+        .iter()
+        .filter_map(|ch| (*ch as char).to_digit(16))
+        .map(|digit| digit as u8)
+        .collect::<Vec<_>>();
+    // an odd number of digits is padded with zero
+    if digits.len() % 2 == 1 {
+        digits.push(0);
+    }
+    digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+}
+
+fn decode_literal_string(raw: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let ch = raw[i];
+        i += 1;
+        if ch != b'\\' {
+            result.push(ch);
+            continue;
+        }
+        let escaped = match raw.get(i) {
+            Some(escaped) => *escaped,
+            None => break,
+        };
+        i += 1;
+        match escaped {
+            b'n' => result.push(b'\n'),
+            b'r' => result.push(b'\r'),
+            b't' => result.push(b'\t'),
+            b'b' => result.push(b'\x08'),
+            b'f' => result.push(b'\x0c'),
+            b'0'..=b'7' => {
+                let mut value = (escaped - b'0') as u32;
+                let mut count = 1;
+                while count < 3 && i < raw.len() && (b'0'..=b'7').contains(&raw[i]) {
+                    value = value * 8 + (raw[i] - b'0') as u32;
+                    i += 1;
+                    count += 1;
+                }
+                result.push(value as u8);
+            }
+            // line continuation
+            b'\r' => {
+                if raw.get(i) == Some(&b'\n') {
+                    i += 1;
+                }
+            }
+            b'\n' => {}
+            other => result.push(other),
+        }
+    }
+    result
+}
@@ -0,0 +1,259 @@
+/// Content stream token types
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash)]
+pub enum QPdfTokenType {
+    Bad,
+    ArrayOpen,
+    ArrayClose,
+    BraceOpen,
+    BraceClose,
+    DictOpen,
+    DictClose,
+    Integer,
+    Real,
+    Name,
+    String,
+    Null,
+    Bool,
+    Operator,
+    InlineImage,
+}
+
+/// Single token of the content stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct QPdfToken {
+    token_type: QPdfTokenType,
+    raw: Vec<u8>,
+}
+
+impl QPdfToken {
+    /// Get token type
+    pub fn token_type(&self) -> QPdfTokenType {
+        self.token_type
+    }
+
+    /// Get raw token bytes as they appear in the stream
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+}
+
+/// Tokenizer for the content stream data, which yields tokens with their type and raw bytes.
+/// Whitespace and comments are skipped, the data between the `ID` and `EI` operators is returned
+/// as a single `InlineImage` token.
+///
+/// This is a pure Rust implementation, not a wrapper of the QPDF `QPDFTokenizer`, which the QPDF C API
+/// does not expose. Its results may differ from QPDF on malformed content.
+pub struct QPdfTokenizer<'a> {
+    data: &'a [u8],
+    pos: usize,
+    inline_image: bool,
+}
+
+impl<'a> QPdfTokenizer<'a> {
+    /// Create a tokenizer for the given data
+    pub fn new(data: &'a [u8]) -> Self {
+        QPdfTokenizer {
+            data,
+            pos: 0,
+            inline_image: false,
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while self.pos < self.data.len() {
+            let ch = self.data[self.pos];
+            if is_whitespace(ch) {
+                self.pos += 1;
+            } else if ch == b'%' {
+                while self.pos < self.data.len() && !matches!(self.data[self.pos], b'\r' | b'\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_inline_image(&mut self) -> Option<QPdfToken> {
+        self.inline_image = false;
+
+        // a single whitespace character separates the ID operator from the image data
+        if self.pos < self.data.len() && is_whitespace(self.data[self.pos]) {
+            self.pos += 1;
+        }
+
+        let start = self.pos;
+        let mut end = self.data.len();
+        let mut i = start;
+        while i + 2 <= self.data.len() {
+            if &self.data[i..i + 2] == b"EI"
+                && (i == start || is_whitespace(self.data[i - 1]))
+                && (i + 2 == self.data.len() || is_whitespace(self.data[i + 2]) || is_delimiter(self.data[i + 2]))
+            {
+                end = i;
+                break;
+            }
+            i += 1;
+        }
+
+        self.pos = end;
+        let mut data_end = end;
+        if data_end > start && is_whitespace(self.data[data_end - 1]) {
+            data_end -= 1;
+        }
+        Some(self.make_token(QPdfTokenType::InlineImage, start, data_end))
+    }
+
+    fn read_literal_string(&mut self) -> QPdfTokenType {
+        let mut depth = 0;
+        while self.pos < self.data.len() {
+            let ch = self.data[self.pos];
+            self.pos += 1;
+            match ch {
+                b'\\' => self.pos += 1,
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return QPdfTokenType::String;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.pos = self.data.len();
+        QPdfTokenType::Bad
+    }
+
+    fn read_hex_string(&mut self) -> QPdfTokenType {
+        self.pos += 1;
+        while self.pos < self.data.len() {
+            let ch = self.data[self.pos];
+            self.pos += 1;
+            if ch == b'>' {
+                return QPdfTokenType::String;
+            } else if !ch.is_ascii_hexdigit() && !is_whitespace(ch) {
+                return QPdfTokenType::Bad;
+            }
+        }
+        QPdfTokenType::Bad
+    }
+
+    fn read_regular(&mut self) {
+        while self.pos < self.data.len() && !is_whitespace(self.data[self.pos]) && !is_delimiter(self.data[self.pos]) {
+            self.pos += 1;
+        }
+    }
+
+    fn make_token(&self, token_type: QPdfTokenType, start: usize, end: usize) -> QPdfToken {
+        QPdfToken {
+            token_type,
+            raw: self.data[start..end].to_vec(),
+        }
+    }
+}
+
+impl<'a> Iterator for QPdfTokenizer<'a> {
+    type Item = QPdfToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.inline_image {
+            return self.read_inline_image();
+        }
+
+        self.skip_whitespace_and_comments();
+
+        let start = self.pos;
+        let ch = *self.data.get(start)?;
+        let next = self.data.get(start + 1).copied();
+
+        let token_type = match (ch, next) {
+            (b'[', _) => {
+                self.pos += 1;
+                QPdfTokenType::ArrayOpen
+            }
+            (b']', _) => {
+                self.pos += 1;
+                QPdfTokenType::ArrayClose
+            }
+            (b'{', _) => {
+                self.pos += 1;
+                QPdfTokenType::BraceOpen
+            }
+            (b'}', _) => {
+                self.pos += 1;
+                QPdfTokenType::BraceClose
+            }
+            (b'<', Some(b'<')) => {
+                self.pos += 2;
+                QPdfTokenType::DictOpen
+            }
+            (b'>', Some(b'>')) => {
+                self.pos += 2;
+                QPdfTokenType::DictClose
+            }
+            (b'<', _) => self.read_hex_string(),
+            (b'(', _) => self.read_literal_string(),
+            (b'/', _) => {
+                self.pos += 1;
+                self.read_regular();
+                QPdfTokenType::Name
+            }
+            (b')', _) | (b'>', _) => {
+                self.pos += 1;
+                QPdfTokenType::Bad
+            }
+            _ => {
+                self.read_regular();
+                classify_word(&self.data[start..self.pos])
+            }
+        };
+
+        let token = self.make_token(token_type, start, self.pos);
+        if token_type == QPdfTokenType::Operator && token.raw == b"ID" {
+            self.inline_image = true;
+        }
+        Some(token)
+    }
+}
+
+fn is_whitespace(ch: u8) -> bool {
+    matches!(ch, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn is_delimiter(ch: u8) -> bool {
+    matches!(ch, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+fn classify_word(word: &[u8]) -> QPdfTokenType {
+    let digits = word
+        .strip_prefix(b"+")
+        .or_else(|| word.strip_prefix(b"-"))
+        .unwrap_or(word);
+    let num_digits = digits.iter().filter(|ch| ch.is_ascii_digit()).count();
+    let num_dots = digits.iter().filter(|&&ch| ch == b'.').count();
+
+    match word {
+        b"true" | b"false" => QPdfTokenType::Bool,
+        b"null" => QPdfTokenType::Null,
+        _ if num_digits > 0 && num_digits == digits.len() => QPdfTokenType::Integer,
+        _ if num_digits > 0 && num_dots == 1 && num_digits + 1 == digits.len() => QPdfTokenType::Real,
+        _ => QPdfTokenType::Operator,
+    }
+}
+
+// Serialize tokens back into the content stream, each operator is placed on a separate line
+pub(crate) fn tokens_to_bytes<'a, I>(tokens: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = &'a QPdfToken>,
+{
+    let mut result = Vec::new();
+    for token in tokens {
+        result.extend_from_slice(&token.raw);
+        match token.token_type {
+            QPdfTokenType::Operator | QPdfTokenType::InlineImage => result.push(b'\n'),
+            _ => result.push(b' '),
+        }
+    }
+    result
+}
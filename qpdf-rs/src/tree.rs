@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use crate::{QPdf, QPdfArray, QPdfDictionary, QPdfObject, QPdfObjectLike, QPdfScalar};
+
+type ObjGen = (u32, u32);
+
+/// Read-only view of the name tree, such as the `/Dests` or `/EmbeddedFiles` entry of the catalog `/Names`
+pub struct QPdfNameTree {
+    root: QPdfDictionary,
+}
+
+impl QPdfNameTree {
+    /// Create a name tree view from its root node
+    pub fn new(root: QPdfDictionary) -> Self {
+        QPdfNameTree { root }
+    }
+
+    /// Get the root node of the tree
+    pub fn root(&self) -> &QPdfDictionary {
+        &self.root
+    }
+
+    /// Find the value for the given key. Intermediate nodes whose `/Limits` do not include the key are skipped.
+    pub fn get(&self, key: &str) -> Option<QPdfObject> {
+        let mut result = None;
+        walk_tree(
+            &self.root,
+            "/Names",
+            &|limits| name_in_limits(key, limits),
+            &mut |name, value| {
+                if name.as_string() == key {
+                    result = Some(value);
+                    false
+                } else {
+                    true
+                }
+            },
+        );
+        result
+    }
+
+    /// Return all (key, value) pairs of the tree in the tree order
+    pub fn iter(&self) -> std::vec::IntoIter<(String, QPdfObject)> {
+        let mut result = Vec::new();
+        walk_tree(&self.root, "/Names", &|_| true, &mut |name, value| {
+            result.push((name.as_string(), value));
+            true
+        });
+        result.into_iter()
+    }
+}
+
+/// Read-only view of the number tree, such as the catalog `/PageLabels` or the structure tree `/ParentTree`
+pub struct QPdfNumberTree {
+    root: QPdfDictionary,
+}
+
+impl QPdfNumberTree {
+    /// Create a number tree view from its root node
+    pub fn new(root: QPdfDictionary) -> Self {
+        QPdfNumberTree { root }
+    }
+
+    /// Get the root node of the tree
+    pub fn root(&self) -> &QPdfDictionary {
+        &self.root
+    }
+
+    /// Find the value for the given key. Intermediate nodes whose `/Limits` do not include the key are skipped.
+    pub fn get(&self, key: i64) -> Option<QPdfObject> {
+        let mut result = None;
+        walk_tree(
+            &self.root,
+            "/Nums",
+            &|limits| number_in_limits(key, limits),
+            &mut |number, value| {
+                if number_key(&number) == Some(key) {
+                    result = Some(value);
+                    false
+                } else {
+                    true
+                }
+            },
+        );
+        result
+    }
+
+    /// Return all (key, value) pairs of the tree in the tree order, entries with non-integer keys are skipped
+    pub fn iter(&self) -> std::vec::IntoIter<(i64, QPdfObject)> {
+        let mut result = Vec::new();
+        walk_tree(&self.root, "/Nums", &|_| true, &mut |number, value| {
+            if let Some(number) = number_key(&number) {
+                result.push((number, value));
+            }
+            true
+        });
+        result.into_iter()
+    }
+}
+
+impl QPdf {
+    /// Get a number tree view for the given root node. If the root is not a dictionary the tree is empty.
+    pub fn get_number_tree(&self, root: &QPdfObject) -> QPdfNumberTree {
+        let root = if root.is_dictionary() {
+            root.clone().into()
+        } else {
+            self.new_dictionary()
+        };
+        QPdfNumberTree::new(root)
+    }
+
+    /// Get the name tree from the catalog `/Names` dictionary, for example "Dests" or "EmbeddedFiles".
+    /// The name may be given with or without the leading slash.
+    pub fn get_name_tree(&self, name: &str) -> Option<QPdfNameTree> {
+        let key = if name.starts_with('/') {
+            name.to_owned()
+        } else {
+            format!("/{}", name)
+        };
+        let names = self.get_root()?.get("/Names").filter(|names| names.is_dictionary())?;
+        QPdfDictionary::from(names)
+            .get(&key)
+            .filter(|tree| tree.is_dictionary())
+            .map(|tree| QPdfNameTree::new(tree.into()))
+    }
+}
+
+fn name_in_limits(key: &str, limits: &QPdfArray) -> bool {
+    match (limits.get(0), limits.get(1)) {
+        (Some(low), Some(high)) if low.is_string() && high.is_string() => {
+            low.as_string().as_str() <= key && key <= high.as_string().as_str()
+        }
+        _ => true,
+    }
+}
+
+fn number_key(obj: &QPdfObject) -> Option<i64> {
+    if obj.is_integer() {
+        Some(QPdfScalar::from(obj.clone()).as_i64())
+    } else {
+        None
+    }
+}
+
+fn number_in_limits(key: i64, limits: &QPdfArray) -> bool {
+    match (
+        limits.get(0).as_ref().and_then(number_key),
+        limits.get(1).as_ref().and_then(number_key),
+    ) {
+        (Some(low), Some(high)) => low <= key && key <= high,
+        _ => true,
+    }
+}
+
+// Walk the tree nodes depth first, the kids for which `in_limits` returns false are skipped.
+// The leaf array `leaf_key` is a flat list of keys and values, `visit` returns false to stop the walk.
+fn walk_tree<L, V>(root: &QPdfDictionary, leaf_key: &str, in_limits: &L, visit: &mut V)
+where
+    L: Fn(&QPdfArray) -> bool,
+    V: FnMut(QPdfObject, QPdfObject) -> bool,
+{
+    let mut visited = HashSet::new();
+    walk_node(root, leaf_key, in_limits, visit, &mut visited);
+}
+
+fn walk_node<L, V>(
+    node: &QPdfDictionary,
+    leaf_key: &str,
+    in_limits: &L,
+    visit: &mut V,
+    visited: &mut HashSet<ObjGen>,
+) -> bool
+where
+    L: Fn(&QPdfArray) -> bool,
+    V: FnMut(QPdfObject, QPdfObject) -> bool,
+{
+    // malformed trees may contain loops
+    if node.is_indirect() && !visited.insert((node.get_id(), node.get_generation())) {
+        return true;
+    }
+
+    if let Some(leaves) = node.get(leaf_key).filter(|obj| obj.is_array()) {
+        let leaves = QPdfArray::from(leaves);
+        let mut items = leaves.iter();
+        while let (Some(key), Some(value)) = (items.next(), items.next()) {
+            if !visit(key, value) {
+                return false;
+            }
+        }
+    }
+
+    if let Some(kids) = node.get("/Kids").filter(|obj| obj.is_array()) {
+        for kid in QPdfArray::from(kids).iter() {
+            if !kid.is_dictionary() {
+                continue;
+            }
+            let kid = QPdfDictionary::from(kid);
+            let in_range = kid
+                .get("/Limits")
+                .filter(|limits| limits.is_array())
+                .map(|limits| in_limits(&QPdfArray::from(limits)))
+                .unwrap_or(true);
+            if in_range && !walk_node(&kid, leaf_key, in_limits, visit, visited) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
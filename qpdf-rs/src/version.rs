@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::QPdf;
+
+/// PDF version which is compared numerically, so 1.10 is greater than 1.5
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PdfVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl PdfVersion {
+    /// Create a version from major and minor numbers
+    pub fn new(major: u32, minor: u32) -> Self {
+        PdfVersion { major, minor }
+    }
+
+    /// Parse a version string such as "1.7". Anything after the minor number, like the extension level
+    /// in "1.7.3" or "1.7 extension level 3", is ignored.
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, rest) = version.trim().split_once('.')?;
+        let minor_len = rest.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(rest.len());
+        Some(PdfVersion {
+            major: major.parse().ok()?,
+            minor: rest[..minor_len].parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for PdfVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl QPdf {
+    /// Get PDF version of the document, the default value 0.0 is returned if the version can not be parsed
+    pub fn pdf_version(&self) -> PdfVersion {
+        PdfVersion::parse(&self.get_pdf_version()).unwrap_or_default()
+    }
+}
@@ -1,6 +1,14 @@
-use std::{ffi::CString, path::Path, slice};
-
-use crate::{ObjectStreamMode, QPdf, Result, StreamDataMode, StreamDecodeLevel};
+use std::{
+    ffi::{CString, OsString},
+    fs,
+    path::Path,
+    slice,
+};
+
+use crate::{
+    EncryptionParams, ObjectStreamMode, PdfVersion, QPdf, QPdfError, QPdfErrorCode, Result, StreamDataMode,
+    StreamDecodeLevel,
+};
 
 /// PDF writer with several customizable parameters
 pub struct QPdfWriter {
@@ -17,10 +25,16 @@ pub struct QPdfWriter {
     stream_decode_level: Option<StreamDecodeLevel>,
     object_stream_mode: Option<ObjectStreamMode>,
     stream_data_mode: Option<StreamDataMode>,
+    strip_metadata: Option<bool>,
+    qdf_mode: Option<bool>,
+    encryption_params: Option<EncryptionParams>,
+    encrypt_metadata: Option<bool>,
+    trailer_id: Option<[Vec<u8>; 2]>,
 }
 
 impl QPdfWriter {
     pub(crate) fn new(owner: QPdf) -> Self {
+        let min_pdf_version = owner.inner.min_pdf_version.clone();
         QPdfWriter {
             owner,
             compress_streams: None,
@@ -30,15 +44,31 @@ impl QPdfWriter {
             linearize: None,
             static_id: None,
             deterministic_id: None,
-            min_pdf_version: None,
+            min_pdf_version,
             force_pdf_version: None,
             stream_decode_level: None,
             object_stream_mode: None,
             stream_data_mode: None,
+            strip_metadata: None,
+            qdf_mode: None,
+            encryption_params: None,
+            encrypt_metadata: None,
+            trailer_id: None,
         }
     }
 
     fn process_params(&self) -> Result<()> {
+        // object streams were introduced in PDF 1.5
+        let forced_version = self.force_pdf_version.as_deref().and_then(PdfVersion::parse);
+        if self.object_stream_mode == Some(ObjectStreamMode::Generate)
+            && forced_version.is_some_and(|version| version < PdfVersion::new(1, 5))
+        {
+            return Err(QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                "Object streams can not be generated for PDF versions before 1.5",
+            ));
+        }
+
         unsafe {
             if let Some(compress_streams) = self.compress_streams {
                 qpdf_sys::qpdf_set_compress_streams(self.owner.inner(), compress_streams.into());
@@ -83,6 +113,51 @@ impl QPdfWriter {
                 qpdf_sys::qpdf_set_stream_data_mode(self.owner.inner(), stream_data_mode.as_qpdf_enum());
             }
 
+            if let Some(qdf_mode) = self.qdf_mode {
+                qpdf_sys::qpdf_set_qdf_mode(self.owner.inner(), qdf_mode.into());
+            }
+
+            if let Some(true) = self.strip_metadata {
+                if let Some(trailer) = self.owner.get_trailer() {
+                    trailer.remove("/Info");
+                }
+                if let Some(root) = self.owner.get_root() {
+                    root.remove("/Metadata");
+                }
+            }
+
+            match (&self.encryption_params, self.encrypt_metadata) {
+                (Some(EncryptionParams::R2(_) | EncryptionParams::R3(_)), Some(_)) => {
+                    return Err(QPdfError::new(
+                        QPdfErrorCode::InvalidParameter,
+                        "Metadata encryption cannot be set for R2 and R3 encryption",
+                    ))
+                }
+                (Some(params), encrypt_metadata) => params.apply(&self.owner, encrypt_metadata)?,
+                (None, Some(_)) => {
+                    return Err(QPdfError::new(
+                        QPdfErrorCode::InvalidParameter,
+                        "Metadata encryption is set but no encryption is configured",
+                    ))
+                }
+                (None, None) => {}
+            }
+
+            if let Some(ref id) = self.trailer_id {
+                // the linearized output has offsets after the first trailer which would be broken by resizing
+                if id.iter().any(|part| part.is_empty()) || (self.linearize == Some(true) && id[1].len() != 16) {
+                    return Err(QPdfError::new(QPdfErrorCode::InvalidParameter, "Invalid trailer ID"));
+                }
+                // QPDF keeps the first part of the existing ID, which is also used for the encryption key
+                if let Some(trailer) = self.owner.get_trailer() {
+                    let id = self.owner.new_array_from([
+                        self.owner.new_binary_string(&id[0]),
+                        self.owner.new_binary_string(&id[1]),
+                    ]);
+                    trailer.set("/ID", id);
+                }
+            }
+
             if let Some(ref version) = self.min_pdf_version {
                 let version = CString::new(version.as_str())?;
                 self.owner
@@ -97,15 +172,23 @@ impl QPdfWriter {
         Ok(())
     }
 
-    /// Write PDF to a file
+    /// Write PDF to a file. If a trailer ID is set, the whole output is built in memory first.
     pub fn write<P>(&self, path: P) -> Result<()>
     where
         P: AsRef<Path>,
     {
+        // the second part of the trailer ID can only be replaced in the written output
+        if self.trailer_id.is_some() {
+            fs::write(path, self.write_to_memory()?)?;
+            return Ok(());
+        }
+
         let filename = CString::new(path.as_ref().to_string_lossy().as_ref())?;
 
         let inner = self.owner.inner();
 
+        self.owner.apply_stream_providers()?;
+
         self.owner
             .wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_init_write(inner, filename.as_ptr()) })?;
 
@@ -114,9 +197,76 @@ impl QPdfWriter {
         self.owner.wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_write(inner) })
     }
 
+    /// Write PDF to a temporary file in the same directory and rename it over `path`,
+    /// so that readers never see a partially written file. If the rename fails, for example when
+    /// the target is on another device, the temporary file is copied to `path` and removed instead,
+    /// which is not atomic. The temporary file is removed if writing fails.
+    pub fn write_atomic<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file_name = path.file_name().ok_or_else(|| {
+            QPdfError::new(
+                QPdfErrorCode::InvalidParameter,
+                format!("Invalid file name: {}", path.display()),
+            )
+        })?;
+        let mut temp_name = OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = path.with_file_name(temp_name);
+
+        let result = self.write(&temp_path).and_then(|_| {
+            if fs::rename(&temp_path, path).is_err() {
+                fs::copy(&temp_path, path)?;
+                fs::remove_file(&temp_path)?;
+            }
+            Ok(())
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// Write PDF to a file and return the size of the written file in bytes
+    pub fn write_sized<P>(&self, path: P) -> Result<u64>
+    where
+        P: AsRef<Path>,
+    {
+        self.write(path.as_ref())?;
+        Ok(fs::metadata(path)?.len())
+    }
+
     /// Write PDF to a memory and return it in a Vec
     pub fn write_to_memory(&self) -> Result<Vec<u8>> {
+        let trailer = self.trailer_id.as_ref().and_then(|_| self.owner.get_trailer());
+        let original_id = trailer.as_ref().map(|trailer| trailer.get("/ID"));
+
+        let result = self.write_buffer();
+
+        // the trailer ID is set only for the write, the original one is restored
+        if let (Some(trailer), Some(original_id)) = (trailer, original_id) {
+            match original_id {
+                Some(id) => trailer.set("/ID", id),
+                None => trailer.remove("/ID"),
+            }
+        }
+
+        let data = result?;
+        match self.trailer_id {
+            // the linearized output has the ID in the first page trailer and in the main trailer
+            Some(ref id) => replace_second_id(&data, id, if self.linearize == Some(true) { 2 } else { 1 }),
+            None => Ok(data),
+        }
+    }
+
+    fn write_buffer(&self) -> Result<Vec<u8>> {
         let inner = self.owner.inner();
+
+        self.owner.apply_stream_providers()?;
+
         self.owner
             .wrap_ffi_call(|| unsafe { qpdf_sys::qpdf_init_write_memory(inner) })?;
 
@@ -127,7 +277,7 @@ impl QPdfWriter {
         let buffer = unsafe { qpdf_sys::qpdf_get_buffer(inner) };
         let buffer_len = unsafe { qpdf_sys::qpdf_get_buffer_length(inner) };
 
-        unsafe { Ok(slice::from_raw_parts(buffer as *const u8, buffer_len as _).to_vec()) }
+        Ok(unsafe { slice::from_raw_parts(buffer as *const u8, buffer_len as _).to_vec() })
     }
 
     /// Enable or disable stream compression
@@ -154,7 +304,8 @@ impl QPdfWriter {
         self
     }
 
-    /// Set object stream mode
+    /// Set object stream mode. Writing fails if `ObjectStreamMode::Generate` is combined with
+    /// a forced PDF version before 1.5, which does not support object streams.
     pub fn object_stream_mode(&mut self, mode: ObjectStreamMode) -> &mut Self {
         self.object_stream_mode = Some(mode);
         self
@@ -201,4 +352,77 @@ impl QPdfWriter {
         self.deterministic_id = Some(flag);
         self
     }
+
+    /// Encrypt the output with the specified parameters
+    pub fn encryption_params(&mut self, params: EncryptionParams) -> &mut Self {
+        self.encryption_params = Some(params);
+        self
+    }
+
+    /// Override the metadata encryption flag of the encryption parameters. It applies to R4 and later,
+    /// writing fails if no encryption parameters or R2 or R3 parameters are set.
+    pub fn encrypt_metadata(&mut self, flag: bool) -> &mut Self {
+        self.encrypt_metadata = Some(flag);
+        self
+    }
+
+    /// Enable or disable QDF mode which produces uncompressed, normalized and commented output for inspection
+    pub fn qdf_mode(&mut self, flag: bool) -> &mut Self {
+        self.qdf_mode = Some(flag);
+        self
+    }
+
+    /// Set a fixed trailer `/ID` instead of the generated one. The first part is set in the document trailer
+    /// for the duration of the write, so it is also used for the encryption key, and the original `/ID` is restored
+    /// afterwards; the second part, which QPDF always generates, is replaced in the output. The output is
+    /// therefore always built in memory. For linearized output the second part must be 16 bytes long.
+    pub fn trailer_id(&mut self, id: [Vec<u8>; 2]) -> &mut Self {
+        self.trailer_id = Some(id);
+        self
+    }
+
+    /// Remove the `/Info` dictionary and the XMP `/Metadata` stream of the catalog when writing.
+    /// Note that they are removed from the document itself, not only from the output.
+    pub fn strip_metadata(&mut self, flag: bool) -> &mut Self {
+        self.strip_metadata = Some(flag);
+        self
+    }
+}
+
+fn hex_encode(data: &[u8]) -> Vec<u8> {
+    data.iter().flat_map(|b| format!("{:02x}", b).into_bytes()).collect()
+}
+
+// Replace the second part of the `/ID [<first><second>]` entries written by QPDF, failing
+// if the entries are not found the expected number of times
+fn replace_second_id(data: &[u8], id: &[Vec<u8>; 2], expected: usize) -> Result<Vec<u8>> {
+    let mut prefix = b"/ID [<".to_vec();
+    prefix.extend(hex_encode(&id[0]));
+    prefix.extend_from_slice(b"><");
+
+    let mut result = Vec::with_capacity(data.len());
+    let mut pos = 0;
+    let mut count = 0;
+    while let Some(start) = data[pos..].windows(prefix.len()).position(|w| w == prefix.as_slice()) {
+        let second = pos + start + prefix.len();
+        let end = match data[second..].iter().position(|&ch| ch == b'>') {
+            Some(end) => second + end,
+            None => break,
+        };
+        result.extend_from_slice(&data[pos..second]);
+        result.extend(hex_encode(&id[1]));
+        pos = end;
+        count += 1;
+    }
+    if count != expected {
+        return Err(QPdfError::new(
+            QPdfErrorCode::InternalError,
+            format!(
+                "Expected {} trailer ID entries in the output, found {}",
+                expected, count
+            ),
+        ));
+    }
+    result.extend_from_slice(&data[pos..]);
+    Ok(result)
 }
@@ -276,3 +276,2538 @@ fn test_pdf_encrypted() {
     let qpdf = QPdf::read_from_memory_encrypted(&data, "test");
     assert!(qpdf.is_ok());
 }
+
+#[test]
+fn test_swap_objects() {
+    let qpdf = QPdf::empty();
+    let first = qpdf.parse_object("[1 2 3]").unwrap().into_indirect();
+    let second = qpdf.parse_object("<< /Key /Value >>").unwrap().into_indirect();
+
+    qpdf.swap_objects(
+        first.get_id(),
+        first.get_generation(),
+        second.get_id(),
+        second.get_generation(),
+    )
+    .unwrap();
+
+    let swapped_first = qpdf.get_object_by_id(first.get_id(), first.get_generation()).unwrap();
+    assert_eq!(swapped_first.get_type(), QPdfObjectType::Dictionary);
+    assert_eq!(
+        QPdfDictionary::from(swapped_first).get("/Key").unwrap().as_name(),
+        "/Value"
+    );
+
+    let swapped_second = qpdf.get_object_by_id(second.get_id(), second.get_generation()).unwrap();
+    assert_eq!(swapped_second.get_type(), QPdfObjectType::Array);
+    assert_eq!(QPdfArray::from(swapped_second).len(), 3);
+
+    let stream = qpdf.new_stream(b"data");
+    assert!(qpdf
+        .swap_objects(
+            first.get_id(),
+            first.get_generation(),
+            stream.get_id(),
+            stream.get_generation()
+        )
+        .is_err());
+}
+
+#[test]
+fn test_swap_streams() {
+    let qpdf = QPdf::empty();
+    let first = qpdf.new_stream_with_dictionary([("/Type", qpdf.new_name("/First"))], b"first data");
+    let second = qpdf.new_stream(b"");
+    second
+        .try_replace_data(b"7365636f6e642064617461>", StreamFilter::AsciiHex, qpdf.new_null())
+        .unwrap();
+    let holder = qpdf.new_dictionary_from([("/A", first.as_object().clone()), ("/B", second.as_object().clone())]);
+
+    qpdf.swap_objects(
+        first.get_id(),
+        first.get_generation(),
+        second.get_id(),
+        second.get_generation(),
+    )
+    .unwrap();
+
+    let a = QPdfStream::from(holder.get("/A").unwrap());
+    let b = QPdfStream::from(holder.get("/B").unwrap());
+    assert_eq!(
+        a.get_data(StreamDecodeLevel::Generalized).unwrap().as_ref(),
+        b"second data"
+    );
+    assert_eq!(a.get_dictionary().get("/Filter").unwrap().as_name(), "/ASCIIHexDecode");
+    assert!(a.get_dictionary().get("/Type").is_none());
+    assert_eq!(b.get_data(StreamDecodeLevel::None).unwrap().as_ref(), b"first data");
+    assert_eq!(b.get_dictionary().get("/Type").unwrap().as_name(), "/First");
+    assert!(b.get_dictionary().get("/Filter").is_none());
+}
+
+#[test]
+fn test_disable_print_scaling() {
+    let qpdf = QPdf::empty();
+    qpdf.disable_print_scaling().unwrap();
+    qpdf.disable_print_scaling().unwrap();
+
+    let prefs: QPdfDictionary = qpdf.get_root().unwrap().get("/ViewerPreferences").unwrap().into();
+    assert_eq!(prefs.get("/PrintScaling").unwrap().as_name(), "/None");
+    assert_eq!(prefs.keys().len(), 1);
+}
+
+#[test]
+fn test_pages_to_memory() {
+    let qpdf = load_pdf();
+    let mem = qpdf.pages_to_memory(&[1]).unwrap();
+
+    let extracted = QPdf::read_from_memory(&mem).unwrap();
+    assert_eq!(extracted.get_num_pages().unwrap(), 1);
+
+    let expected = qpdf.get_page(1).unwrap().get_page_content_data().unwrap();
+    let actual = extracted.get_page(0).unwrap().get_page_content_data().unwrap();
+    assert_eq!(actual.as_ref(), expected.as_ref());
+
+    let result = qpdf.pages_to_memory(&[0, 5]);
+    assert_eq!(result.unwrap_err().error_code(), QPdfErrorCode::PagesError);
+}
+
+#[test]
+fn test_pages_to_memory_inherited_attributes() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    page.remove("/MediaBox");
+    let pages: QPdfDictionary = qpdf.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/MediaBox", qpdf.parse_object("[0 0 200 300]").unwrap());
+    pages.set("/Rotate", qpdf.new_integer(90));
+
+    let mem = qpdf.pages_to_memory(&[0]).unwrap();
+    assert!(page.get("/MediaBox").is_none());
+    assert!(page.get("/Rotate").is_none());
+
+    let extracted = QPdf::read_from_memory(&mem).unwrap();
+    let page = extracted.get_page(0).unwrap();
+    assert_eq!(
+        QPdfArray::from(page.get("/MediaBox").unwrap()).as_f64_vec(),
+        Some(vec![0.0, 0.0, 200.0, 300.0])
+    );
+    assert_eq!(page.get("/Rotate").unwrap().as_number(), Some(90.0));
+}
+
+#[test]
+fn test_checked_names() {
+    let qpdf = QPdf::empty();
+
+    let name = QPdfName::new("/My Name").unwrap();
+    assert_eq!(name.as_str(), "/My Name");
+    assert_eq!(name.escaped(), "/My#20Name");
+    assert_eq!(QPdfName::new("/My#20Name").unwrap(), name);
+
+    let obj = qpdf.new_name_checked("/My Name").unwrap();
+    assert_eq!(obj.to_string(), "/My#20Name");
+
+    let obj = qpdf.new_name_checked("/Type").unwrap();
+    assert_eq!(obj.as_name(), "/Type");
+    assert_eq!(obj.to_string(), "/Type");
+
+    assert!(qpdf.new_name_checked("Type").is_err());
+    assert!(qpdf.new_name_checked("/Bad#2").is_err());
+    assert!(qpdf.new_name_checked("/Bad#zz").is_err());
+}
+
+#[test]
+fn test_empty_with_version() {
+    let qpdf = QPdf::empty_with_version("2.0");
+    let mem = qpdf.writer().write_to_memory().unwrap();
+
+    let mem_pdf = QPdf::read_from_memory(&mem).unwrap();
+    assert_eq!(mem_pdf.get_pdf_version(), "2.0");
+}
+
+#[test]
+fn test_uses_shadings() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    assert!(!page.uses_shadings());
+
+    let contents = qpdf.new_stream(b"q /Sh1 sh Q\n");
+    page.set("/Contents", &contents);
+    assert!(page.uses_shadings());
+
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let shading = qpdf
+        .parse_object("<< /ShadingType 2 /ColorSpace /DeviceRGB /Coords [0 0 1 0] >>")
+        .unwrap();
+    let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+    resources.set("/Shading", qpdf.new_dictionary_from([("/Sh1", shading)]));
+    assert!(page.uses_shadings());
+}
+
+#[test]
+fn test_pages_iterator() {
+    let qpdf = load_pdf();
+    assert_eq!(qpdf.pages().unwrap().count(), 2);
+
+    let page = qpdf.pages().unwrap().nth(1).unwrap();
+    assert_eq!(page.get_id(), qpdf.get_page(1).unwrap().get_id());
+
+    assert!(qpdf.pages().unwrap().nth(2).is_none());
+}
+
+#[test]
+fn test_output_intents() {
+    let qpdf = QPdf::empty();
+    assert!(qpdf.output_intents().unwrap().is_empty());
+
+    let mut profile = vec![0u8; 128];
+    profile[16..20].copy_from_slice(b"CMYK");
+
+    let intent = OutputIntent {
+        subtype: "/GTS_PDFX".to_owned(),
+        output_condition_identifier: "FOGRA39".to_owned(),
+        dest_output_profile: Some(profile),
+    };
+    qpdf.add_output_intent(intent.clone()).unwrap();
+
+    let mem = qpdf.writer().write_to_memory().unwrap();
+    let mem_pdf = QPdf::read_from_memory(&mem).unwrap();
+
+    let intents = mem_pdf.output_intents().unwrap();
+    assert_eq!(intents, vec![intent]);
+}
+
+#[test]
+fn test_validate_structure() {
+    let qpdf = QPdf::empty();
+    let page = qpdf
+        .parse_object("<< /Type /Page /MediaBox [0 0 612 792] /Resources << >> >>")
+        .unwrap();
+    qpdf.add_page(page.into_indirect(), false).unwrap();
+    qpdf.validate_structure().unwrap();
+
+    let root = qpdf.get_root().unwrap();
+    root.remove("/Pages");
+    let result = qpdf.validate_structure();
+    assert_eq!(result.as_ref().unwrap_err().error_code(), QPdfErrorCode::DamagedPdf);
+    assert!(result.unwrap_err().description().is_some());
+}
+
+#[test]
+fn test_flatten_optional_content() {
+    let qpdf = QPdf::empty();
+    let visible = qpdf
+        .parse_object("<< /Type /OCG /Name (Visible) >>")
+        .unwrap()
+        .into_indirect();
+    let hidden = qpdf
+        .parse_object("<< /Type /OCG /Name (Hidden) >>")
+        .unwrap()
+        .into_indirect();
+
+    let contents =
+        qpdf.new_stream(b"/OC /L1 BDC 0 0 10 10 re f EMC\n/OC /L2 BDC BT (hidden) Tj ET EMC\nBT (shown) Tj ET\n");
+    let properties = qpdf.new_dictionary_from([("/L1", visible.clone()), ("/L2", hidden.clone())]);
+    let resources = qpdf.new_dictionary_from([("/Properties", properties)]);
+    let page = qpdf.new_dictionary_from([
+        ("/Type", qpdf.new_name("/Page")),
+        ("/MediaBox", qpdf.parse_object("[0 0 612 792]").unwrap()),
+        ("/Contents", contents.into()),
+        ("/Resources", resources.into()),
+    ]);
+    qpdf.add_page(page.into_indirect(), false).unwrap();
+
+    let config = qpdf.new_dictionary_from([("/OFF", qpdf.new_array_from([hidden.clone()]))]);
+    let oc_properties = qpdf.new_dictionary_from([
+        ("/OCGs", QPdfObject::from(qpdf.new_array_from([visible, hidden]))),
+        ("/D", config.into()),
+    ]);
+    qpdf.get_root().unwrap().set("/OCProperties", oc_properties);
+
+    qpdf.flatten_optional_content().unwrap();
+
+    assert!(!qpdf.get_root().unwrap().has("/OCProperties"));
+
+    let data = qpdf.get_page(0).unwrap().get_page_content_data().unwrap();
+    let content = String::from_utf8_lossy(&data);
+    assert!(content.contains("re"));
+    assert!(content.contains("(shown)"));
+    assert!(!content.contains("(hidden)"));
+    assert!(!content.contains("BDC"));
+    assert!(!content.contains("EMC"));
+}
+
+#[test]
+fn test_two_page_spread() {
+    let qpdf = QPdf::empty();
+    assert_eq!(qpdf.get_two_page_spread(), None);
+
+    qpdf.set_two_page_spread(true).unwrap();
+    assert_eq!(qpdf.get_two_page_spread(), Some(true));
+    assert_eq!(
+        qpdf.get_root().unwrap().get("/PageLayout").unwrap().as_name(),
+        "/TwoPageRight"
+    );
+
+    qpdf.set_two_page_spread(false).unwrap();
+    assert_eq!(qpdf.get_two_page_spread(), Some(false));
+
+    qpdf.set_single_page_layout().unwrap();
+    assert_eq!(qpdf.get_two_page_spread(), None);
+    assert_eq!(
+        qpdf.get_root().unwrap().get("/PageLayout").unwrap().as_name(),
+        "/SinglePage"
+    );
+}
+
+#[test]
+fn test_object_owner() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+
+    let owner = page.as_object().owner();
+    page.set("/MyKey", owner.new_name("/MyValue"));
+
+    let page = qpdf.get_page(0).unwrap();
+    assert_eq!(page.get("/MyKey").unwrap().as_name(), "/MyValue");
+}
+
+#[test]
+fn test_fingerprint() {
+    let first = load_pdf();
+    let second = load_pdf();
+
+    let info: QPdfDictionary = second.get_trailer().unwrap().get("/Info").unwrap().into();
+    info.set("/Title", second.new_utf8_string("Changed title"));
+    second.get_trailer().unwrap().remove("/ID");
+
+    assert_eq!(first.fingerprint().unwrap(), second.fingerprint().unwrap());
+
+    let page = second.get_page(1).unwrap();
+    page.set("/Contents", second.new_stream(b"BT /F1 12 Tf (Changed) Tj ET"));
+    assert_ne!(first.fingerprint().unwrap(), second.fingerprint().unwrap());
+}
+
+#[test]
+fn test_add_blank_page() {
+    let qpdf = load_pdf();
+    let page = qpdf.add_blank_page(595.28, 841.89, false).unwrap();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 3);
+    assert_eq!(qpdf.get_page(2).unwrap().get_id(), page.get_id());
+
+    let media_box: QPdfArray = page.get("/MediaBox").unwrap().into();
+    assert_eq!(
+        media_box
+            .iter()
+            .map(|v| QPdfScalar::from(v).as_f64())
+            .collect::<Vec<_>>(),
+        vec![0.0, 0.0, 595.28, 841.89]
+    );
+    assert!(page.get_page_content_data().unwrap().is_empty());
+}
+
+#[test]
+fn test_make_direct() {
+    let qpdf = QPdf::empty();
+    let media_box = qpdf
+        .new_array_from([
+            QPdfObject::from(qpdf.new_integer(0)),
+            qpdf.new_integer(0).into(),
+            qpdf.new_integer(612).into(),
+            qpdf.new_integer(792).into(),
+        ])
+        .into_indirect();
+    assert!(media_box.is_indirect());
+
+    let direct = media_box.make_direct().unwrap();
+    assert!(!direct.is_indirect());
+    assert!(media_box.is_indirect());
+    assert_eq!(direct.to_string(), "[ 0 0 612 792 ]");
+
+    let array: QPdfArray = qpdf.new_array().into_indirect().into();
+    array.push(&array);
+    assert!(array.make_direct().is_err());
+}
+
+#[test]
+fn test_error_classification() {
+    let err = QPdf::read("tests/data/encrypted.pdf").unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidPassword);
+    assert!(err.is_password_required());
+    assert!(err.is_retryable());
+
+    assert!(!err.is_wrong_password());
+
+    let err = QPdf::read_encrypted("tests/data/encrypted.pdf", "wrong").unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidPassword);
+    assert!(!err.is_password_required());
+    assert!(err.is_wrong_password());
+    assert!(err.is_retryable());
+
+    let data = std::fs::read("tests/data/encrypted.pdf").unwrap();
+    let err = QPdf::read_from_memory_encrypted(&data, "").unwrap_err();
+    assert!(err.is_password_required());
+    let err = QPdf::read_from_memory_encrypted(&data, "wrong").unwrap_err();
+    assert!(err.is_wrong_password());
+
+    let err = QPdf::read("tests/data/no_such_file.pdf").unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::SystemError);
+    assert!(err.description().unwrap().contains("no_such_file.pdf"));
+    assert!(!err.is_password_required());
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn test_read_with_recovery() {
+    let data = std::fs::read("tests/data/test.pdf").unwrap();
+    let startxref = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+
+    let path = std::env::temp_dir().join("qpdf_rs_truncated.pdf");
+    std::fs::write(&path, &data[..startxref]).unwrap();
+
+    let (qpdf, warnings) = QPdf::read_with_recovery(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+    assert!(!warnings.is_empty());
+    assert!(warnings.iter().any(|w| w.error_code() == QPdfErrorCode::DamagedPdf));
+}
+
+#[test]
+fn test_object_type_names() {
+    assert_eq!(QPdfObjectType::InlineImage.as_str(), "inlineimage");
+    assert_eq!(QPdfObjectType::InlineImage.to_string(), "inlineimage");
+    assert_eq!(QPdfObjectType::Dictionary.to_string(), "dictionary");
+
+    let qpdf = load_pdf();
+    assert_eq!(qpdf.get_root().unwrap().get_type().to_string(), "dictionary");
+}
+
+#[test]
+fn test_replace_stream_data_with_filter() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let contents: QPdfStream = page.get("/Contents").unwrap().into();
+    let encoded = contents.get_data(StreamDecodeLevel::None).unwrap();
+    let decoded = contents.get_data(StreamDecodeLevel::Generalized).unwrap();
+    assert_ne!(encoded.as_ref(), decoded.as_ref());
+
+    let stream = qpdf.new_stream([]);
+    stream
+        .try_replace_data(&encoded, StreamFilter::Flate, qpdf.new_null())
+        .unwrap();
+    assert_eq!(
+        stream.get_dictionary().get("/Filter").unwrap().as_name(),
+        "/FlateDecode"
+    );
+    assert_eq!(
+        stream.get_data(StreamDecodeLevel::Generalized).unwrap().as_ref(),
+        decoded.as_ref()
+    );
+
+    // a filter chain is passed as an array of filter names
+    let hex = |data: &[u8]| {
+        let mut encoded = data
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+            .into_bytes();
+        encoded.push(b'>');
+        encoded
+    };
+    stream.replace_data(
+        hex(&hex(b"chain")),
+        qpdf.parse_object("[/ASCIIHexDecode /ASCIIHexDecode]").unwrap(),
+        qpdf.new_null(),
+    );
+    assert_eq!(
+        stream.get_filters(),
+        Some(vec![StreamFilter::AsciiHex, StreamFilter::AsciiHex])
+    );
+    assert_eq!(
+        stream.get_data(StreamDecodeLevel::Generalized).unwrap().as_ref(),
+        b"chain"
+    );
+}
+
+#[test]
+fn test_get_or_insert_with() {
+    let qpdf = QPdf::empty();
+    let resources = qpdf.new_dictionary();
+    resources.set("/Font", qpdf.new_null());
+
+    let fonts: QPdfDictionary = resources
+        .get_or_insert_with("/Font", || qpdf.new_dictionary().into())
+        .into();
+    fonts.set("/F1", qpdf.new_name("/Helvetica"));
+
+    let fonts: QPdfDictionary = resources
+        .get_or_insert_with("/Font", || panic!("value must exist"))
+        .into();
+    assert_eq!(fonts.get("/F1").unwrap().as_name(), "/Helvetica");
+    assert_eq!(resources.keys(), vec!["/Font".to_owned()]);
+}
+
+#[test]
+fn test_get_all_objects() {
+    let qpdf = load_pdf();
+    let objects = qpdf.get_all_objects();
+    assert!(qpdf.object_count() > 0);
+    assert_eq!(qpdf.object_count(), objects.len());
+    assert!(objects.iter().all(|obj| obj.get_id() != 0));
+    assert!(objects
+        .iter()
+        .any(|obj| obj.get_id() == qpdf.get_root().unwrap().get_id()));
+
+    qpdf.new_integer(42).into_indirect();
+    let mem = qpdf
+        .writer()
+        .preserve_unreferenced_objects(true)
+        .object_stream_mode(ObjectStreamMode::Disable)
+        .write_to_memory()
+        .unwrap();
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    assert!(copy
+        .get_all_objects()
+        .iter()
+        .any(|obj| obj.as_number() == Some(42.0) && obj.get_id() != 0));
+}
+
+#[test]
+fn test_object_stream_info() {
+    let qpdf = load_pdf();
+    let mem = qpdf
+        .writer()
+        .object_stream_mode(ObjectStreamMode::Generate)
+        .write_to_memory()
+        .unwrap();
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+
+    let info = copy.object_stream_info().unwrap();
+    assert_eq!(info.len(), copy.object_count());
+    assert!(info.iter().any(|entry| entry.in_object_stream));
+    // the object streams themselves are written directly
+    assert!(info.iter().any(|entry| !entry.in_object_stream));
+    let root_id = copy.get_root().unwrap().get_id();
+    assert!(info.iter().any(|entry| entry.id == root_id && entry.in_object_stream));
+
+    let info = qpdf
+        .writer()
+        .object_stream_mode(ObjectStreamMode::Disable)
+        .write_to_memory()
+        .map(|mem| QPdf::read_from_memory(mem).unwrap().object_stream_info().unwrap())
+        .unwrap();
+    assert!(info.iter().all(|entry| !entry.in_object_stream));
+}
+
+#[test]
+fn test_recompress_streams() {
+    let write = |qpdf: &QPdf| {
+        qpdf.writer()
+            .stream_data_mode(StreamDataMode::Preserve)
+            .object_stream_mode(ObjectStreamMode::Disable)
+            .write_to_memory()
+            .unwrap()
+    };
+
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let content = b"0 0 m 612 792 l S\n".repeat(1000);
+    page.set("/Contents", qpdf.new_stream(&content).into_indirect());
+    let uncompressed = write(&qpdf);
+
+    qpdf.recompress_streams(StreamDataMode::Compress).unwrap();
+    let compressed = write(&qpdf);
+    assert!(compressed.len() < uncompressed.len());
+
+    let qpdf = QPdf::read_from_memory(&compressed).unwrap();
+    let contents: QPdfStream = qpdf.get_page(0).unwrap().get("/Contents").unwrap().into();
+    assert_eq!(contents.get_filters(), Some(vec![StreamFilter::Flate]));
+    assert_eq!(
+        contents.get_data(StreamDecodeLevel::Generalized).unwrap().as_ref(),
+        content
+    );
+
+    qpdf.recompress_streams(StreamDataMode::Uncompress).unwrap();
+    assert_eq!(contents.get_filters(), Some(vec![]));
+    assert_eq!(contents.get_data(StreamDecodeLevel::None).unwrap().as_ref(), content);
+}
+
+#[test]
+fn test_structural_eq() {
+    let qpdf = QPdf::empty();
+    let dict1 = qpdf
+        .parse_object("<< /Type /Page /MediaBox [0 0 612 792] /Rotate 90 /Label (abc) >>")
+        .unwrap();
+    let dict2 = qpdf
+        .parse_object("<< /Label (abc) /MediaBox [0 0 612 792] /Rotate 90 /Type /Page /Extra null >>")
+        .unwrap();
+    assert_ne!(dict1, dict2);
+    assert!(dict1.structural_eq(&dict2));
+
+    let other = QPdf::empty();
+    let dict3 = other
+        .parse_object("<< /Type /Page /MediaBox [0 0 612 792] /Rotate 90 /Label (abd) >>")
+        .unwrap();
+    assert!(!dict1.structural_eq(&dict3));
+
+    let indirect = dict1.clone().into_indirect();
+    assert!(indirect.structural_eq(&dict2));
+
+    let array1: QPdfArray = qpdf.new_array().into_indirect().into();
+    array1.push(&array1);
+    let array2: QPdfArray = qpdf.new_array().into_indirect().into();
+    array2.push(&array2);
+    assert!(array1.as_object().structural_eq(array2.as_object()));
+}
+
+#[test]
+fn test_merge() {
+    let merged = QPdf::merge([load_pdf(), load_pdf()]).unwrap();
+    assert_eq!(merged.get_num_pages().unwrap(), 4);
+
+    let mem = merged.writer().write_to_memory().unwrap();
+    drop(merged);
+
+    let qpdf = QPdf::read_from_memory(&mem).unwrap();
+    let source = load_pdf();
+    for (index, page) in qpdf.get_pages().unwrap().iter().enumerate() {
+        let source_page = source.get_page(index as u32 % 2).unwrap();
+        assert_eq!(
+            page.get_page_content_data().unwrap().as_ref(),
+            source_page.get_page_content_data().unwrap().as_ref()
+        );
+        let resources = QPdfDictionary::from(page.get("/Resources").unwrap());
+        assert!(resources.get("/Font").is_some());
+    }
+}
+
+#[test]
+fn test_strip_metadata() {
+    let qpdf = load_pdf();
+    let info: QPdfDictionary = qpdf.get_trailer().unwrap().get("/Info").unwrap().into();
+    info.set("/Title", qpdf.new_utf8_string("Title"));
+    let metadata = qpdf.new_stream(b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>");
+    qpdf.get_root().unwrap().set("/Metadata", metadata.into_indirect());
+
+    let mem = qpdf.writer().strip_metadata(false).write_to_memory().unwrap();
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    let info: QPdfDictionary = copy.get_trailer().unwrap().get("/Info").unwrap().into();
+    assert_eq!(info.get("/Title").unwrap().as_string(), "Title");
+
+    let mem = qpdf.writer().strip_metadata(true).write_to_memory().unwrap();
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    assert!(copy.get_trailer().unwrap().get("/Info").is_none());
+    assert!(copy.get_root().unwrap().get("/Metadata").is_none());
+}
+
+#[test]
+fn test_qdf_mode() {
+    let qpdf = load_pdf();
+    let mem = qpdf.writer().qdf_mode(true).write_to_memory().unwrap();
+    let text = String::from_utf8_lossy(&mem);
+    assert!(text.contains("%QDF-1.0"));
+    assert!(text.contains("%% Original object ID:"));
+    assert!(text.contains("1 0 obj"));
+    assert!(text.contains("BT"));
+}
+
+#[test]
+fn test_stream_data_provider() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let contents = qpdf.new_stream([]);
+    page.set("/Contents", contents.into_indirect());
+
+    let contents: QPdfStream = page.get("/Contents").unwrap().into();
+    contents.replace_data_on_write(|| b"0 0 m 612 792 l S".to_vec(), StreamFilter::None, qpdf.new_null());
+    assert!(contents.get_data(StreamDecodeLevel::None).unwrap().is_empty());
+
+    let mem = qpdf
+        .writer()
+        .stream_data_mode(StreamDataMode::Uncompress)
+        .write_to_memory()
+        .unwrap();
+    assert!(mem.windows(17).any(|w| w == b"0 0 m 612 792 l S"));
+
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    assert_eq!(
+        copy.get_page(0).unwrap().get_page_content_data().unwrap().as_ref(),
+        b"0 0 m 612 792 l S"
+    );
+}
+
+#[test]
+fn test_page_boxes() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let pages: QPdfDictionary = page.get("/Parent").unwrap().into();
+    pages.set("/MediaBox", qpdf.parse_object("[0 0 595 842]").unwrap());
+    page.remove("/MediaBox");
+    page.set("/TrimBox", qpdf.parse_object("[10 10 585.5 832]").unwrap());
+
+    let boxes = qpdf.get_page_boxes(&page);
+    assert_eq!(boxes.media_box, Some([0.0, 0.0, 595.0, 842.0]));
+    assert_eq!(boxes.crop_box, None);
+    assert_eq!(boxes.effective_crop_box(), Some([0.0, 0.0, 595.0, 842.0]));
+    assert_eq!(boxes.trim_box, Some([10.0, 10.0, 585.5, 832.0]));
+    assert_eq!(boxes.bleed_box, None);
+    assert_eq!(boxes.art_box, None);
+
+    let boxes = qpdf.get_page_boxes(&qpdf.get_page(1).unwrap());
+    assert_eq!(boxes.media_box, Some([0.0, 0.0, 612.0, 792.0]));
+}
+
+#[test]
+fn test_rect() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.new_dictionary();
+    page.set_rect("/MediaBox", [0.0, 0.0, 612.0, 792.5]);
+    let media_box: QPdfArray = page.get("/MediaBox").unwrap().into();
+    assert_eq!(
+        media_box.iter().map(|v| v.get_type()).collect::<Vec<_>>(),
+        vec![
+            QPdfObjectType::Integer,
+            QPdfObjectType::Integer,
+            QPdfObjectType::Integer,
+            QPdfObjectType::Real
+        ]
+    );
+    assert_eq!(page.get_rect("/MediaBox"), Some([0.0, 0.0, 612.0, 792.5]));
+
+    page.set("/CropBox", qpdf.parse_object("[0 0 /Bad 1]").unwrap());
+    assert_eq!(page.get_rect("/CropBox"), None);
+    assert_eq!(page.get_rect("/TrimBox"), None);
+}
+
+#[test]
+fn test_try_close() {
+    let qpdf = load_pdf();
+    assert!(qpdf.try_close().is_ok());
+
+    let qpdf = load_pdf();
+    let root = qpdf.get_root().unwrap();
+    let qpdf = qpdf.try_close().unwrap_err();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+
+    drop(root);
+    assert!(qpdf.try_close().is_ok());
+}
+
+#[test]
+fn test_read_encrypted_retry() {
+    let mut attempts = Vec::new();
+    let qpdf = QPdf::read_encrypted_retry("tests/data/encrypted.pdf", |attempt| {
+        attempts.push(attempt);
+        match attempt {
+            0 => Some("wrong".to_owned()),
+            1 => Some("test".to_owned()),
+            _ => None,
+        }
+    })
+    .unwrap();
+    assert_eq!(attempts, vec![0, 1]);
+    assert!(qpdf.get_num_pages().unwrap() > 0);
+
+    let err = QPdf::read_encrypted_retry("tests/data/encrypted.pdf", |_| None).unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidPassword);
+    assert!(err.is_password_required());
+
+    let qpdf = QPdf::read_encrypted_retry("tests/data/test.pdf", |_| panic!("password is not needed")).unwrap();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+}
+
+#[test]
+fn test_as_number() {
+    let qpdf = QPdf::empty();
+    assert_eq!(qpdf.new_integer(42).as_number(), Some(42.0));
+    assert_eq!(qpdf.parse_object("-3.25").unwrap().as_number(), Some(-3.25));
+    assert_eq!(qpdf.new_name("/Name").as_number(), None);
+    assert_eq!(qpdf.new_null().as_number(), None);
+}
+
+#[test]
+fn test_move_and_reverse_pages() {
+    let qpdf = load_pdf();
+    let ids = |qpdf: &QPdf| qpdf.get_pages().unwrap().iter().map(|p| p.get_id()).collect::<Vec<_>>();
+    let original = ids(&qpdf);
+
+    qpdf.reverse_pages().unwrap();
+    assert_eq!(ids(&qpdf), original.iter().rev().copied().collect::<Vec<_>>());
+
+    qpdf.move_page(1, 0).unwrap();
+    assert_eq!(ids(&qpdf), original);
+
+    qpdf.move_page(0, 2).unwrap();
+    assert_eq!(ids(&qpdf), vec![original[1], original[0]]);
+
+    qpdf.move_page(0, 1).unwrap();
+    assert_eq!(ids(&qpdf), vec![original[1], original[0]]);
+
+    assert_eq!(
+        qpdf.move_page(2, 0).unwrap_err().error_code(),
+        QPdfErrorCode::PagesError
+    );
+    assert_eq!(
+        qpdf.move_page(0, 3).unwrap_err().error_code(),
+        QPdfErrorCode::PagesError
+    );
+}
+
+#[test]
+fn test_overlay_inherited_resources() {
+    let inherit_resources = |qpdf: &QPdf| {
+        let page = qpdf.add_blank_page(200.0, 100.0, false).unwrap();
+        page.remove("/Resources");
+        let resources = qpdf
+            .parse_object("<< /Font << /F1 << /Type /Font /Subtype /Type1 /BaseFont /Helvetica >> >> >>")
+            .unwrap()
+            .into_indirect();
+        let pages: QPdfDictionary = qpdf.get_root().unwrap().get("/Pages").unwrap().into();
+        pages.set("/Resources", &resources);
+        (page, resources)
+    };
+
+    let stamp = QPdf::empty();
+    let (stamp_page, _) = inherit_resources(&stamp);
+    stamp_page.set("/Contents", stamp.new_stream("BT /F1 12 Tf (stamp) Tj ET"));
+
+    let qpdf = QPdf::empty();
+    let (page, shared) = inherit_resources(&qpdf);
+    qpdf.overlay(&stamp, 0, false).unwrap();
+
+    // the stamp page tree is not changed
+    assert!(stamp_page.get("/Resources").is_none());
+
+    // the page has its own resources with the inherited font, the shared ones are not changed
+    let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+    assert_ne!(resources.get_id(), shared.get_id());
+    assert!(resources.get("/Font").is_some());
+    let xobjects: QPdfDictionary = resources.get("/XObject").unwrap().into();
+    let form: QPdfStream = xobjects.get(&xobjects.keys()[0]).unwrap().into();
+    let form_fonts = QPdfDictionary::from(form.get_dictionary().get("/Resources").unwrap()).get("/Font");
+    assert!(form_fonts.is_some());
+    assert!(QPdfDictionary::from(shared).get("/XObject").is_none());
+}
+
+#[test]
+fn test_overlay() {
+    let stamp = QPdf::empty();
+    let stamp_page = stamp.add_blank_page(200.0, 100.0, false).unwrap();
+    stamp_page.set("/Contents", stamp.new_stream("0 0 m 200 100 l S").into_indirect());
+
+    let qpdf = load_pdf();
+    qpdf.overlay(&stamp, 0, false).unwrap();
+    drop(stamp);
+
+    let mem = qpdf.writer().write_to_memory().unwrap();
+    let qpdf = QPdf::read_from_memory(&mem).unwrap();
+
+    let mut form_ids = Vec::new();
+    for page in qpdf.get_pages().unwrap() {
+        let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+        let xobjects: QPdfDictionary = resources.get("/XObject").unwrap().into();
+        let forms = xobjects
+            .keys()
+            .into_iter()
+            .filter_map(|key| xobjects.get(&key))
+            .filter(|xobject| {
+                QPdfStream::from(xobject.clone())
+                    .get_dictionary()
+                    .get("/Subtype")
+                    .map(|s| s.as_name())
+                    .as_deref()
+                    == Some("/Form")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(forms.len(), 1);
+        form_ids.push(forms[0].get_id());
+
+        let form: QPdfStream = forms[0].clone().into();
+        assert_eq!(
+            form.get_data(StreamDecodeLevel::Generalized).unwrap().as_ref(),
+            b"0 0 m 200 100 l S"
+        );
+
+        let content = page.get_page_content_data().unwrap();
+        assert!(String::from_utf8_lossy(&content).contains(" Do"));
+    }
+    assert_eq!(form_ids.len(), 2);
+    assert_eq!(form_ids[0], form_ids[1]);
+}
+
+#[test]
+fn test_nup() {
+    let qpdf = load_pdf();
+    let result = qpdf.nup(2, 1).unwrap();
+    assert_eq!(result.get_num_pages().unwrap(), 1);
+
+    let sheet = result.get_page(0).unwrap();
+    let resources: QPdfDictionary = sheet.get("/Resources").unwrap().into();
+    let xobjects: QPdfDictionary = resources.get("/XObject").unwrap().into();
+    assert_eq!(xobjects.keys().len(), 2);
+    let content = sheet.get_page_content_data().unwrap();
+    assert_eq!(String::from_utf8_lossy(&content).matches(" Do").count(), 2);
+    assert_eq!(result.get_page_boxes(&sheet).media_box, Some([0.0, 0.0, 612.0, 792.0]));
+
+    let mem = result.writer().write_to_memory().unwrap();
+    assert_eq!(QPdf::read_from_memory(&mem).unwrap().get_num_pages().unwrap(), 1);
+
+    qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let result = qpdf.nup(2, 1).unwrap();
+    assert_eq!(result.get_num_pages().unwrap(), 2);
+    let content = result.get_page(1).unwrap().get_page_content_data().unwrap();
+    assert_eq!(String::from_utf8_lossy(&content).matches(" Do").count(), 1);
+
+    assert!(qpdf.nup(0, 1).is_err());
+}
+
+#[test]
+fn test_nup_inherited_attributes() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    page.remove("/MediaBox");
+    let pages: QPdfDictionary = qpdf.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/MediaBox", qpdf.parse_object("[0 0 300 400]").unwrap());
+
+    let result = qpdf.nup(1, 1).unwrap();
+    let sheet = result.get_page(0).unwrap();
+    assert_eq!(result.get_page_boxes(&sheet).media_box, Some([0.0, 0.0, 300.0, 400.0]));
+    assert!(page.get("/MediaBox").is_none());
+}
+
+#[test]
+fn test_nup_rotated_pages() {
+    let qpdf = QPdf::empty();
+    for _ in 0..2 {
+        let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+        page.set("/Rotate", qpdf.new_integer(90));
+    }
+
+    let result = qpdf.nup(1, 2).unwrap();
+    assert_eq!(result.get_num_pages().unwrap(), 1);
+    let sheet = result.get_page(0).unwrap();
+    assert_eq!(result.get_page_boxes(&sheet).media_box, Some([0.0, 0.0, 792.0, 612.0]));
+
+    let xobjects: QPdfDictionary = QPdfDictionary::from(sheet.get("/Resources").unwrap())
+        .get("/XObject")
+        .unwrap()
+        .into();
+    for key in xobjects.keys() {
+        let form: QPdfStream = xobjects.get(&key).unwrap().into();
+        assert_eq!(
+            QPdfArray::from(form.get_dictionary().get("/Matrix").unwrap()).as_f64_vec(),
+            Some(vec![0.0, -1.0, 1.0, 0.0, 0.0, 612.0])
+        );
+    }
+
+    let operators = result.parse_content(&sheet.get_page_content_data().unwrap()).unwrap();
+    let cm = operators.iter().find(|op| op.operator == "cm").unwrap();
+    assert_eq!(
+        cm.operands.iter().map(|v| v.as_number().unwrap()).collect::<Vec<_>>(),
+        vec![0.5, 0.0, 0.0, 0.5, 198.0, 306.0]
+    );
+}
+
+#[test]
+fn test_resolve() {
+    let qpdf = QPdf::empty();
+    let dict = qpdf
+        .new_dictionary_from([("/Key", qpdf.new_integer(42))])
+        .into_indirect();
+    let array = qpdf.new_array_from([dict.clone(), qpdf.new_integer(1).into()]);
+
+    let item = array.get(0).unwrap();
+    assert!(item.is_indirect());
+    let resolved = item.resolve();
+    assert_eq!(resolved.get_type(), QPdfObjectType::Dictionary);
+    assert_eq!(resolved.get_id(), dict.get_id());
+    assert_eq!(
+        QPdfDictionary::from(resolved).get("/Key").unwrap().as_number(),
+        Some(42.0)
+    );
+
+    let direct = array.get(1).unwrap();
+    assert_eq!(direct.resolve().as_number(), Some(1.0));
+}
+
+#[test]
+fn test_encrypt_metadata() {
+    let xmp = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>";
+    let qpdf = load_pdf();
+    let metadata = qpdf.new_stream_with_dictionary(
+        [
+            ("/Type", qpdf.new_name("/Metadata")),
+            ("/Subtype", qpdf.new_name("/XML")),
+        ],
+        xmp,
+    );
+    qpdf.get_root().unwrap().set("/Metadata", metadata.into_indirect());
+
+    let params = EncryptionParams::R6(EncryptionParamsR6 {
+        user_password: "user".to_owned(),
+        owner_password: "owner".to_owned(),
+        allow_print: PrintPermission::Full,
+        encrypt_metadata: true,
+        ..Default::default()
+    });
+    let write = |encrypt_metadata: bool| {
+        qpdf.writer()
+            .encryption_params(params.clone())
+            .encrypt_metadata(encrypt_metadata)
+            .stream_data_mode(StreamDataMode::Preserve)
+            .write_to_memory()
+            .unwrap()
+    };
+    let contains_xmp = |data: &[u8]| data.windows(xmp.len()).any(|w| w == xmp);
+
+    let mem = write(true);
+    assert!(!contains_xmp(&mem));
+
+    let mem = write(false);
+    assert!(contains_xmp(&mem));
+    let copy = QPdf::read_from_memory_encrypted(&mem, "user").unwrap();
+    assert!(copy.is_encrypted());
+
+    assert!(qpdf.writer().encrypt_metadata(false).write_to_memory().is_err());
+
+    let r3 = EncryptionParams::R3(EncryptionParamsR3 {
+        user_password: "user".to_owned(),
+        owner_password: "owner".to_owned(),
+        ..Default::default()
+    });
+    let err = qpdf
+        .writer()
+        .encryption_params(r3)
+        .encrypt_metadata(false)
+        .write_to_memory()
+        .unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+}
+
+#[test]
+fn test_decrypt_to_memory() {
+    let qpdf = QPdf::read_encrypted("tests/data/encrypted.pdf", "test").unwrap();
+    assert!(qpdf.is_encrypted());
+
+    let mem = qpdf.decrypt_to_memory().unwrap();
+    let decrypted = QPdf::read_from_memory(&mem).unwrap();
+    assert!(!decrypted.is_encrypted());
+    assert_eq!(decrypted.get_num_pages().unwrap(), qpdf.get_num_pages().unwrap());
+}
+
+#[test]
+fn test_get_raw_data() {
+    let content = b"0 0 m 612 792 l S\n".repeat(100);
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    page.set("/Contents", qpdf.new_stream(&content).into_indirect());
+    let mem = qpdf.writer().compress_streams(true).write_to_memory().unwrap();
+
+    let qpdf = QPdf::read_from_memory(&mem).unwrap();
+    let contents: QPdfStream = qpdf.get_page(0).unwrap().get("/Contents").unwrap().into();
+    assert_eq!(contents.get_filters(), Some(vec![StreamFilter::Flate]));
+
+    let raw = contents.get_raw_data().unwrap();
+    let decoded = contents.get_data(StreamDecodeLevel::Generalized).unwrap();
+    assert_eq!(decoded.as_ref(), content);
+    assert_ne!(raw.as_ref(), decoded.as_ref());
+    assert!(raw.len() < decoded.len());
+}
+
+#[test]
+fn test_import_page() {
+    let qpdf = QPdf::empty();
+    let page = {
+        let source = load_pdf();
+        qpdf.import_page(&source, 1, false).unwrap()
+    };
+    assert_eq!(qpdf.get_num_pages().unwrap(), 1);
+    assert_eq!(qpdf.get_page(0).unwrap().get_id(), page.get_id());
+    assert!(page.get("/Resources").is_some());
+
+    let mem = qpdf.writer().write_to_memory().unwrap();
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    assert_eq!(
+        copy.get_page(0).unwrap().get_page_content_data().unwrap().as_ref(),
+        load_pdf()
+            .get_page(1)
+            .unwrap()
+            .get_page_content_data()
+            .unwrap()
+            .as_ref()
+    );
+
+    assert!(qpdf.import_page(&load_pdf(), 5, false).is_err());
+}
+
+#[test]
+fn test_import_page_inherited_attributes() {
+    let source = QPdf::empty();
+    let source_page = source.add_blank_page(612.0, 792.0, false).unwrap();
+    source_page.remove("/MediaBox");
+    let pages: QPdfDictionary = source.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/MediaBox", source.parse_object("[0 0 300 400]").unwrap());
+    pages.set("/Rotate", source.new_integer(90));
+
+    let qpdf = QPdf::empty();
+    let page = qpdf.import_page(&source, 0, false).unwrap();
+    assert_eq!(page.get_rect("/MediaBox"), Some([0.0, 0.0, 300.0, 400.0]));
+    assert_eq!(page.get("/Rotate").unwrap().as_number(), Some(90.0));
+
+    // the source page still inherits the attributes
+    assert!(source_page.get("/MediaBox").is_none());
+    assert!(source_page.get("/Rotate").is_none());
+}
+
+#[test]
+fn test_content_builder() {
+    let mut builder = ContentBuilder::new();
+    builder
+        .begin_text()
+        .set_font("/F1", 15.0)
+        .move_text(72.0, 720.5)
+        .show_text("Hello (world) \\")
+        .end_text()
+        .set_rgb(1.0, 0.0, 0.25)
+        .rectangle(10.0, 20.0, 100.0, 50.0)
+        .fill();
+
+    let expected = "BT\n/F1 15 Tf\n72 720.5 Td\n(Hello \\(world\\) \\\\) Tj\nET\n1 0 0.25 rg\n10 20 100 50 re\nf\n";
+    assert_eq!(builder.as_bytes(), expected.as_bytes());
+
+    let qpdf = QPdf::empty();
+    let stream = qpdf.new_stream(&builder);
+    assert_eq!(
+        stream.get_data(StreamDecodeLevel::None).unwrap().as_ref(),
+        expected.as_bytes()
+    );
+    assert_eq!(builder.build(), expected.as_bytes());
+}
+
+#[test]
+fn test_name_tree() {
+    let qpdf = QPdf::empty();
+    let leaf = |names: &[(&str, i64)]| {
+        let limits = qpdf.new_array_from([qpdf.new_string(names[0].0), qpdf.new_string(names[names.len() - 1].0)]);
+        let items = qpdf.new_array();
+        for (key, value) in names {
+            items.push(qpdf.new_string(key));
+            items.push(qpdf.new_integer(*value));
+        }
+        qpdf.new_dictionary_from([("/Limits", QPdfObject::from(limits)), ("/Names", items.into())])
+            .into_indirect()
+    };
+    let kids = qpdf.new_array_from([
+        leaf(&[("chapter1", 1), ("chapter2", 2)]),
+        leaf(&[("index", 3), ("toc", 4)]),
+    ]);
+    let dests = qpdf.new_dictionary_from([("/Kids", kids)]);
+    let names = qpdf.new_dictionary_from([("/Dests", dests.into_indirect())]);
+    qpdf.get_root().unwrap().set("/Names", names);
+
+    let tree = qpdf.get_name_tree("Dests").unwrap();
+    assert_eq!(tree.get("chapter2").unwrap().as_number(), Some(2.0));
+    assert_eq!(tree.get("toc").unwrap().as_number(), Some(4.0));
+    assert!(tree.get("appendix").is_none());
+    assert_eq!(
+        tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+        vec!["chapter1", "chapter2", "index", "toc"]
+    );
+
+    assert!(qpdf.get_name_tree("/Dests").is_some());
+    assert!(qpdf.get_name_tree("EmbeddedFiles").is_none());
+}
+
+#[test]
+fn test_number_tree() {
+    let qpdf = QPdf::empty();
+    let leaf = |key: i64, value: &str| {
+        qpdf.new_dictionary_from([
+            (
+                "/Limits",
+                QPdfObject::from(qpdf.new_array_from([qpdf.new_integer(key).into(), qpdf.new_integer(key).into()])),
+            ),
+            (
+                "/Nums",
+                qpdf.new_array_from([qpdf.new_integer(key).into(), qpdf.new_string(value)])
+                    .into(),
+            ),
+        ])
+        .into_indirect()
+    };
+    let kids = qpdf.new_array_from([leaf(0, "first"), leaf(5, "sixth")]);
+    let root = qpdf.new_dictionary_from([("/Kids", kids)]).into_indirect();
+
+    let tree = qpdf.get_number_tree(&root);
+    assert_eq!(tree.get(0).unwrap().as_string(), "first");
+    assert_eq!(tree.get(5).unwrap().as_string(), "sixth");
+    assert!(tree.get(3).is_none());
+    assert_eq!(tree.iter().map(|(key, _)| key).collect::<Vec<_>>(), vec![0, 5]);
+
+    assert_eq!(qpdf.get_number_tree(&qpdf.new_null()).iter().count(), 0);
+}
+
+#[test]
+fn test_unparse_resolved() {
+    let qpdf = QPdf::empty();
+    let child = qpdf
+        .new_dictionary_from([("/Type", qpdf.new_name("/Child"))])
+        .into_indirect();
+    let parent = qpdf
+        .new_dictionary_from([("/Kid", child.clone()), ("/Value", qpdf.new_integer(5).into())])
+        .into_indirect();
+    QPdfDictionary::from(child.clone()).set("/Parent", &parent);
+
+    let id = parent.get_id();
+    assert_eq!(parent.unparse_resolved(0), format!("{} 0 R", id));
+    assert_eq!(
+        parent.unparse_resolved(1),
+        format!("<< /Kid {} 0 R /Value 5 >>", child.get_id())
+    );
+    assert_eq!(
+        parent.unparse_resolved(5),
+        "<< /Kid << /Parent ... (cycle) /Type /Child >> /Value 5 >>"
+    );
+}
+
+#[test]
+fn test_remove_unused_resources() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let font = || {
+        qpdf.parse_object("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>")
+            .unwrap()
+            .into_indirect()
+    };
+    let fonts = qpdf.new_dictionary_from([("/F1", font()), ("/Unused", font())]);
+    let resources = qpdf.new_dictionary_from([
+        ("/Font", QPdfObject::from(fonts)),
+        ("/ProcSet", qpdf.parse_object("[/PDF /Text]").unwrap()),
+    ]);
+    page.set("/Resources", resources);
+    page.set("/Contents", qpdf.new_stream("BT /F1 12 Tf 72 720 Td (Hello) Tj ET"));
+
+    qpdf.remove_unused_resources().unwrap();
+
+    let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+    let fonts: QPdfDictionary = resources.get("/Font").unwrap().into();
+    assert_eq!(fonts.keys(), vec!["/F1".to_owned()]);
+    assert!(resources.get("/ProcSet").is_some());
+}
+
+#[test]
+fn test_flatten_annotations() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    page.set("/Contents", qpdf.new_stream("0 0 m 100 100 l S"));
+
+    let appearance = qpdf.new_stream_with_dictionary(
+        [
+            ("/Type", qpdf.new_name("/XObject")),
+            ("/Subtype", qpdf.new_name("/Form")),
+            ("/BBox", qpdf.parse_object("[0 0 50 20]").unwrap()),
+        ],
+        "1 0 0 rg 0 0 50 20 re f",
+    );
+    let appearance = appearance.into_indirect();
+    let ap = qpdf.new_dictionary_from([("/N", appearance.clone())]);
+    let annot = qpdf.new_dictionary_from([
+        ("/Type", qpdf.new_name("/Annot")),
+        ("/Subtype", qpdf.new_name("/Stamp")),
+        ("/Rect", qpdf.parse_object("[100 100 200 140]").unwrap()),
+        ("/AP", ap.into()),
+    ]);
+    page.set("/Annots", qpdf.new_array_from([annot.into_indirect()]));
+
+    qpdf.flatten_annotations().unwrap();
+
+    assert!(page.get("/Annots").is_none());
+
+    // widgets and links without a visible border are kept
+    let widget = qpdf
+        .parse_object("<< /Type /Annot /Subtype /Widget /FT /Tx /Rect [10 10 50 30] >>")
+        .unwrap()
+        .into_indirect();
+    let link = qpdf
+        .parse_object("<< /Type /Annot /Subtype /Link /Rect [10 40 50 60] /Border [0 0 0] >>")
+        .unwrap()
+        .into_indirect();
+    let bordered = qpdf
+        .parse_object("<< /Type /Annot /Subtype /Link /Rect [10 70 50 90] /Border [0 0 1] >>")
+        .unwrap()
+        .into_indirect();
+    page.set("/Annots", qpdf.new_array_from([widget.clone(), link.clone(), bordered]));
+
+    qpdf.flatten_annotations().unwrap();
+
+    let annots: QPdfArray = page.get("/Annots").unwrap().into();
+    assert_eq!(
+        annots.iter().map(|annot| annot.get_id()).collect::<Vec<_>>(),
+        vec![widget.get_id(), link.get_id()]
+    );
+    let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+    let xobjects: QPdfDictionary = resources.get("/XObject").unwrap().into();
+    let name = xobjects
+        .keys()
+        .into_iter()
+        .find(|key| xobjects.get(key).unwrap().get_id() == appearance.get_id())
+        .unwrap();
+    let content = String::from_utf8(page.get_page_content_data().unwrap().to_vec()).unwrap();
+    assert!(content.contains("0 0 m 100 100 l S"));
+    assert!(content.contains(&format!("2 0 0 2 100 100 cm\n{} Do", name)));
+}
+
+#[test]
+fn test_array_typed_vec() {
+    let qpdf = QPdf::empty();
+    let media_box: QPdfArray = qpdf.parse_object("[0 0 612 792]").unwrap().into();
+    assert_eq!(media_box.as_f64_vec(), Some(vec![0.0, 0.0, 612.0, 792.0]));
+    assert_eq!(media_box.as_name_vec(), None);
+
+    let procset: QPdfArray = qpdf.parse_object("[/PDF /Text]").unwrap().into();
+    assert_eq!(procset.as_name_vec(), Some(vec!["/PDF".to_owned(), "/Text".to_owned()]));
+
+    let mixed: QPdfArray = qpdf.parse_object("[0 0.5 /Name 792]").unwrap().into();
+    assert_eq!(mixed.as_f64_vec(), None);
+    assert_eq!(qpdf.new_array().as_f64_vec(), Some(Vec::new()));
+}
+
+#[test]
+fn test_read_with_options() {
+    let data = std::fs::read("tests/data/test.pdf").unwrap();
+    let startxref = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+
+    let path = std::env::temp_dir().join("qpdf_rs_read_options.pdf");
+    std::fs::write(&path, &data[..startxref]).unwrap();
+
+    let result = QPdf::read_with_options(&path, ReadOptions::new().attempt_recovery(false));
+    assert!(result.is_err());
+
+    let qpdf = QPdf::read_with_options(&path, ReadOptions::new().attempt_recovery(true)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+}
+
+#[test]
+fn test_read_builder() {
+    let data = std::fs::read("tests/data/test.pdf").unwrap();
+    let startxref = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+    let damaged = &data[..startxref];
+
+    assert!(QPdf::builder().attempt_recovery(false).read_memory(damaged).is_err());
+
+    let qpdf = QPdf::builder()
+        .attempt_recovery(true)
+        .ignore_xref_streams(true)
+        .read_memory(damaged)
+        .unwrap();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+
+    let path = std::env::temp_dir().join("qpdf_rs_read_builder.pdf");
+    std::fs::write(&path, damaged).unwrap();
+    let qpdf = QPdf::builder().attempt_recovery(true).read_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(qpdf.get_num_pages().unwrap(), 2);
+
+    let qpdf = QPdf::builder()
+        .password("test")
+        .read_file("tests/data/encrypted.pdf")
+        .unwrap();
+    assert!(qpdf.is_encrypted());
+}
+
+#[test]
+// the hash is based on the object identity which does not change
+#[allow(clippy::mutable_key_type)]
+fn test_object_hash() {
+    let qpdf = load_pdf();
+    let mut set = std::collections::HashSet::new();
+    set.insert(QPdfObject::from(qpdf.get_page(0).unwrap()));
+    set.insert(QPdfObject::from(qpdf.get_page(0).unwrap()));
+    assert_eq!(set.len(), 1);
+
+    set.insert(QPdfObject::from(qpdf.get_page(1).unwrap()));
+    assert_eq!(set.len(), 2);
+
+    let other = load_pdf();
+    set.insert(QPdfObject::from(other.get_page(0).unwrap()));
+    assert_eq!(set.len(), 3);
+
+    assert_ne!(
+        QPdfObject::from(qpdf.new_integer(1)),
+        QPdfObject::from(qpdf.new_integer(1))
+    );
+}
+
+#[test]
+fn test_pdf_version() {
+    assert!(PdfVersion::new(1, 4) < PdfVersion::new(1, 7));
+    assert!(PdfVersion::new(1, 5) < PdfVersion::new(1, 10));
+    assert!(PdfVersion::new(1, 7) < PdfVersion::new(2, 0));
+    assert_eq!(PdfVersion::parse("1.7"), Some(PdfVersion::new(1, 7)));
+    assert_eq!(PdfVersion::parse("1.7.3"), Some(PdfVersion::new(1, 7)));
+    assert_eq!(PdfVersion::parse("1.7 extension level 3"), Some(PdfVersion::new(1, 7)));
+    assert_eq!(PdfVersion::parse("garbage"), None);
+    assert_eq!(PdfVersion::new(2, 0).to_string(), "2.0");
+
+    let qpdf = load_pdf();
+    assert_eq!(qpdf.pdf_version(), PdfVersion::parse(&qpdf.get_pdf_version()).unwrap());
+    assert!(qpdf.pdf_version() >= PdfVersion::new(1, 0));
+}
+
+#[test]
+fn test_write_sized() {
+    let qpdf = load_pdf();
+    let path = std::env::temp_dir().join("qpdf_rs_write_sized.pdf");
+    let size = qpdf.writer().write_sized(&path).unwrap();
+    let actual = std::fs::metadata(&path).unwrap().len();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(size > 0);
+    assert_eq!(size, actual);
+    assert_eq!(size, qpdf.writer().write_to_memory().unwrap().len() as u64);
+}
+
+#[test]
+fn test_linearize_to_memory() {
+    let qpdf = load_pdf();
+    assert!(!qpdf.is_linearized());
+
+    let data = qpdf.linearize_to_memory().unwrap();
+    let linearized = QPdf::read_from_memory(&data).unwrap();
+    assert!(linearized.is_linearized());
+    assert_eq!(linearized.get_num_pages().unwrap(), qpdf.get_num_pages().unwrap());
+}
+
+#[test]
+fn test_type_predicates() {
+    let qpdf = QPdf::empty();
+    let objects: Vec<QPdfObject> = vec![
+        qpdf.new_null(),
+        qpdf.new_bool(true),
+        qpdf.new_integer(1).into(),
+        qpdf.new_name("/Name"),
+        qpdf.new_string("string"),
+        qpdf.new_array().into(),
+        qpdf.new_dictionary().into(),
+        qpdf.new_stream("data").into(),
+    ];
+    let predicates: [fn(&QPdfObject) -> bool; 8] = [
+        |obj| obj.is_null(),
+        |obj| obj.is_bool(),
+        |obj| obj.is_integer(),
+        |obj| obj.is_name(),
+        |obj| obj.is_string(),
+        |obj| obj.is_array(),
+        |obj| obj.is_dictionary(),
+        |obj| obj.is_stream(),
+    ];
+
+    for (i, predicate) in predicates.iter().enumerate() {
+        for (j, obj) in objects.iter().enumerate() {
+            assert_eq!(predicate(obj), i == j, "predicate {} on {}", i, obj.get_type());
+        }
+    }
+
+    assert!(qpdf.new_real(1.5, 2).is_scalar() && !qpdf.new_real(1.5, 2).is_integer());
+    assert!(qpdf.get_root().unwrap().is_dictionary());
+}
+
+#[test]
+fn test_dictionary_merge() {
+    let qpdf = QPdf::empty();
+    let resources = |fonts: &str| {
+        qpdf.parse_object(&format!("<< /Font << {} >> /ProcSet [/PDF /Text] >>", fonts))
+            .unwrap()
+    };
+    let font_names = |dict: &QPdfDictionary| {
+        let fonts: QPdfDictionary = dict.get("/Font").unwrap().into();
+        fonts.keys()
+    };
+
+    let target: QPdfDictionary = resources("/F1 /Helvetica").into();
+    target
+        .merge(&resources("/F2 /Courier").into(), ConflictPolicy::Error)
+        .unwrap();
+    assert_eq!(font_names(&target), vec!["/F1".to_owned(), "/F2".to_owned()]);
+
+    let conflicting: QPdfDictionary = resources("/F1 /Times").into();
+
+    target.merge(&conflicting, ConflictPolicy::Keep).unwrap();
+    let fonts: QPdfDictionary = target.get("/Font").unwrap().into();
+    assert_eq!(fonts.get("/F1").unwrap().as_name(), "/Helvetica");
+
+    assert!(target.merge(&conflicting, ConflictPolicy::Error).is_err());
+
+    target.merge(&conflicting, ConflictPolicy::Overwrite).unwrap();
+    assert_eq!(fonts.get("/F1").unwrap().as_name(), "/Times");
+    assert_eq!(fonts.get("/F2").unwrap().as_name(), "/Courier");
+}
+
+#[test]
+fn test_get_page_images() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let pixels = [255u8, 0, 0, 0, 255, 0, 0, 0, 255];
+    let image = qpdf.new_stream_with_dictionary(
+        [
+            ("/Type", qpdf.new_name("/XObject")),
+            ("/Subtype", qpdf.new_name("/Image")),
+            ("/Width", qpdf.new_integer(3).into()),
+            ("/Height", qpdf.new_integer(1).into()),
+            ("/ColorSpace", qpdf.new_name("/DeviceRGB")),
+            ("/BitsPerComponent", qpdf.new_integer(8).into()),
+        ],
+        pixels,
+    );
+    image
+        .try_replace_data(b"ff0000 00ff00 0000ff>", StreamFilter::AsciiHex, qpdf.new_null())
+        .unwrap();
+    let form = qpdf.new_stream_with_dictionary([("/Subtype", qpdf.new_name("/Form"))], "");
+    let xobjects = qpdf.new_dictionary_from([("/Im1", image.into_indirect()), ("/Fm1", form.into_indirect())]);
+    page.set("/Resources", qpdf.new_dictionary_from([("/XObject", xobjects)]));
+
+    let images = qpdf.get_page_images(&page).unwrap();
+    assert_eq!(images.len(), 1);
+    let image = &images[0];
+    assert_eq!(image.name(), "/Im1");
+    assert_eq!(image.width(), Some(3));
+    assert_eq!(image.height(), Some(1));
+    assert_eq!(image.color_space().as_deref(), Some("/DeviceRGB"));
+    assert_eq!(image.bits_per_component(), Some(8));
+    assert_eq!(image.get_data().unwrap().as_ref(), pixels);
+
+    assert!(qpdf.get_page_images(&load_pdf().get_page(0).unwrap()).is_ok());
+}
+
+#[test]
+fn test_trailer_id() {
+    let qpdf = load_pdf();
+    let original_id = qpdf.get_trailer().unwrap().get("/ID").map(|id| id.to_string());
+    let id = [b"0123456789abcdef".to_vec(), b"reproducible".to_vec()];
+    let mem = qpdf.writer().trailer_id(id.clone()).write_to_memory().unwrap();
+    assert_eq!(
+        qpdf.get_trailer().unwrap().get("/ID").map(|id| id.to_string()),
+        original_id
+    );
+
+    let copy = QPdf::read_from_memory(&mem).unwrap();
+    let trailer_id: QPdfArray = copy.get_trailer().unwrap().get("/ID").unwrap().into();
+    assert_eq!(trailer_id.get(0).unwrap().as_binary_string(), id[0]);
+    assert_eq!(trailer_id.get(1).unwrap().as_binary_string(), id[1]);
+    copy.check_pdf().unwrap();
+
+    let mem2 = qpdf.writer().trailer_id(id.clone()).write_to_memory().unwrap();
+    assert_eq!(mem, mem2);
+
+    assert!(qpdf
+        .writer()
+        .trailer_id([Vec::new(), Vec::new()])
+        .write_to_memory()
+        .is_err());
+}
+
+#[test]
+fn test_replace() {
+    let qpdf = QPdf::empty();
+    let target = qpdf.new_stream("old data").into_indirect();
+
+    qpdf.replace(&target, qpdf.new_integer(42)).unwrap();
+    let resolved = target.resolve();
+    assert!(resolved.is_integer());
+    assert_eq!(resolved.as_number(), Some(42.0));
+
+    let direct = qpdf.new_integer(1).into();
+    assert!(qpdf.replace(&direct, qpdf.new_integer(2)).is_err());
+
+    let foreign = load_pdf().get_root().unwrap().into();
+    assert!(qpdf.replace(&foreign, qpdf.new_integer(2)).is_err());
+}
+
+#[test]
+fn test_stream_data_into_vec() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let expected = page.get_page_content_data().unwrap().as_ref().to_vec();
+
+    let data = page.get_page_content_data().unwrap().into_vec();
+    assert_eq!(data, expected);
+
+    let data: Vec<u8> = page.get_page_content_data().unwrap().into();
+    assert_eq!(data, expected);
+}
+
+#[test]
+fn test_set_trailer_key() {
+    let qpdf = load_pdf();
+    let warning = qpdf.set_trailer_key("/Custom", &qpdf.new_string("value")).unwrap();
+    assert!(warning.is_none());
+    assert_eq!(qpdf.get_trailer().unwrap().get("/Custom").unwrap().as_string(), "value");
+
+    assert!(qpdf.set_trailer_key("/Size", &qpdf.new_integer(10).into()).is_err());
+
+    let root = qpdf.get_root().unwrap();
+    let warning = qpdf.set_trailer_key("/Root", &qpdf.new_dictionary().into()).unwrap();
+    assert!(warning.unwrap().description().contains("/Root"));
+    assert!(qpdf.get_root().unwrap().get("/Type").is_none());
+
+    assert!(qpdf.set_trailer_key("/Root", &root.into()).unwrap().is_none());
+    assert!(QPdf::read_from_memory(qpdf.writer().write_to_memory().unwrap()).is_ok());
+}
+
+#[test]
+fn test_diff() {
+    let qpdf = load_pdf();
+    assert!(qpdf.diff(&load_pdf()).unwrap().is_empty());
+
+    let other = load_pdf();
+    other.remove_page(other.get_page(1).unwrap()).unwrap();
+    assert_eq!(
+        qpdf.diff(&other).unwrap(),
+        vec![PdfDifference::PageRemoved { index: 1 }]
+    );
+    assert_eq!(other.diff(&qpdf).unwrap(), vec![PdfDifference::PageAdded { index: 1 }]);
+
+    other.get_root().unwrap().set("/Lang", other.new_string("en-US"));
+    assert!(qpdf.diff(&other).unwrap().contains(&PdfDifference::CatalogKeyChanged {
+        key: "/Lang".to_owned()
+    }));
+}
+
+#[test]
+fn test_obj() {
+    let qpdf = QPdf::empty();
+    let dict = qpdf.new_dictionary_from([
+        ("/Count", qpdf.obj(42)),
+        ("/Flag", qpdf.obj(true)),
+        ("/Scale", qpdf.obj(0.5)),
+        ("/Whole", qpdf.obj(2.0)),
+        ("/Title", qpdf.obj("Hello (world)")),
+        ("/Rect", qpdf.obj(vec![0, 0, 612, 792])),
+        ("/Name", qpdf.obj(qpdf.new_name("/Value"))),
+    ]);
+
+    assert!(dict.get("/Count").unwrap().is_integer());
+    assert_eq!(dict.get("/Count").unwrap().as_number(), Some(42.0));
+    assert!(dict.get("/Flag").unwrap().as_bool());
+    assert_eq!(dict.get("/Scale").unwrap().as_number(), Some(0.5));
+    assert!(dict.get("/Whole").unwrap().is_integer());
+    assert_eq!(dict.get("/Title").unwrap().as_string(), "Hello (world)");
+    assert_eq!(dict.get_rect("/Rect"), Some([0.0, 0.0, 612.0, 792.0]));
+    assert_eq!(dict.get("/Name").unwrap().as_name(), "/Value");
+
+    let with_null = qpdf.obj("a\0b".to_owned());
+    assert!(with_null.is_string());
+    assert_eq!(with_null.as_binary_string(), b"\xfe\xff\0a\0\0\0b");
+}
+
+#[test]
+fn test_get_inline_images() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    let mut content = b"q 100 0 0 50 72 700 cm\nBI /W 4 /H 2 /CS /RGB /BPC 8 /D [0 1 0 1 0 1] ID ".to_vec();
+    content.extend_from_slice(&[0x45u8; 24]);
+    content.extend_from_slice(b"\nEI Q\n");
+    page.set("/Contents", qpdf.new_stream(&content));
+
+    let images = qpdf.get_inline_images(&page).unwrap();
+    assert_eq!(images.len(), 1);
+    let image = &images[0];
+    assert_eq!(image.width(), Some(4));
+    assert_eq!(image.height(), Some(2));
+    assert_eq!(image.bits_per_component(), Some(8));
+    assert_eq!(image.color_space().as_deref(), Some("/DeviceRGB"));
+    assert_eq!(image.data(), [0x45u8; 24]);
+    assert!(image.dictionary().get("/D").unwrap().is_array());
+
+    assert!(qpdf
+        .get_inline_images(&load_pdf().get_page(0).unwrap())
+        .unwrap()
+        .is_empty());
+
+    // binary lookup string of an indexed color space is kept intact
+    let mut content = b"BI /W 1 /H 1 /BPC 8 /CS [/I /RGB 1 (\xff\x80\x00\xfe\x01\x02)] ID ".to_vec();
+    content.extend_from_slice(&[0x01]);
+    content.extend_from_slice(b"\nEI\n");
+    page.set("/Contents", qpdf.new_stream(&content));
+    let images = qpdf.get_inline_images(&page).unwrap();
+    let color_space = QPdfArray::from(images[0].dictionary().get("/CS").unwrap());
+    assert_eq!(
+        color_space.get(3).unwrap().as_binary_string(),
+        b"\xff\x80\x00\xfe\x01\x02"
+    );
+
+    page.set("/Contents", qpdf.new_stream(b"BI /W 1 /H 1 /BPC 8 /CS /G"));
+    let err = qpdf.get_inline_images(&page).err().unwrap();
+    assert_eq!(err.error_code(), QPdfErrorCode::DamagedPdf);
+}
+
+#[test]
+fn test_tokenizer() {
+    let tokens = QPdfTokenizer::new(b"BT /F1 12 Tf (hi) Tj ET")
+        .map(|token| (token.token_type(), String::from_utf8_lossy(token.raw()).into_owned()))
+        .collect::<Vec<_>>();
+
+    let expected = [
+        (QPdfTokenType::Operator, "BT"),
+        (QPdfTokenType::Name, "/F1"),
+        (QPdfTokenType::Integer, "12"),
+        (QPdfTokenType::Operator, "Tf"),
+        (QPdfTokenType::String, "(hi)"),
+        (QPdfTokenType::Operator, "Tj"),
+        (QPdfTokenType::Operator, "ET"),
+    ];
+    assert_eq!(
+        tokens,
+        expected
+            .iter()
+            .map(|(token_type, raw)| (*token_type, raw.to_string()))
+            .collect::<Vec<_>>()
+    );
+
+    let types = QPdfTokenizer::new(b"[1.5 <414243>] << /K true >> null % comment\n")
+        .map(|token| token.token_type())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            QPdfTokenType::ArrayOpen,
+            QPdfTokenType::Real,
+            QPdfTokenType::String,
+            QPdfTokenType::ArrayClose,
+            QPdfTokenType::DictOpen,
+            QPdfTokenType::Name,
+            QPdfTokenType::Bool,
+            QPdfTokenType::DictClose,
+            QPdfTokenType::Null,
+        ]
+    );
+}
+
+#[test]
+fn test_get_page_text() {
+    let qpdf = QPdf::empty();
+
+    let font = qpdf
+        .parse_object("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+        .unwrap();
+    let contents = qpdf.new_stream(
+        b"BT /F1 15 Tf 72 720 Td (First Page) Tj 0 -20 Td [(Second) -500 (line)] TJ T* <4e6578743a2080> Tj ET\n",
+    );
+    let rfont = qpdf.new_dictionary_from([("/F1", font.into_indirect())]);
+    let resources = qpdf.new_dictionary_from([("/Font", rfont)]);
+    let page = qpdf.new_dictionary_from([
+        ("/Type", qpdf.new_name("/Page")),
+        ("/MediaBox", qpdf.parse_object("[0 0 612 792]").unwrap()),
+        ("/Contents", contents.into()),
+        ("/Resources", resources.into()),
+    ]);
+    qpdf.add_page(page.into_indirect(), true).unwrap();
+
+    let page = qpdf.get_page(0).unwrap();
+    let text = qpdf.get_page_text(&page).unwrap();
+    assert!(text.contains("First Page"));
+    assert_eq!(text, "First Page\nSecond line\nNext: €");
+}
+
+#[test]
+fn test_set_crop_box() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    page.set_rect("/MediaBox", [0.0, 0.0, 612.0, 792.0]);
+
+    qpdf.set_crop_box(&page, [36.0, 36.0, 576.0, 756.5], BoxBoundsPolicy::Error)
+        .unwrap();
+    assert_eq!(qpdf.get_page_boxes(&page).crop_box, Some([36.0, 36.0, 576.0, 756.5]));
+
+    let err = qpdf
+        .set_crop_box(&page, [-10.0, 36.0, 700.0, 756.0], BoxBoundsPolicy::Error)
+        .unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+
+    qpdf.set_crop_box(&page, [700.0, 36.0, -10.0, 800.0], BoxBoundsPolicy::Clamp)
+        .unwrap();
+    assert_eq!(qpdf.get_page_boxes(&page).crop_box, Some([0.0, 36.0, 612.0, 792.0]));
+
+    assert!(qpdf
+        .set_crop_box(&page, [700.0, 800.0, 900.0, 900.0], BoxBoundsPolicy::Clamp)
+        .is_err());
+
+    qpdf.crop_all_pages([10.0, 10.0, 500.0, 700.0], BoxBoundsPolicy::Clamp)
+        .unwrap();
+    for page in qpdf.get_pages().unwrap() {
+        assert_eq!(qpdf.get_page_boxes(&page).crop_box, Some([10.0, 10.0, 500.0, 700.0]));
+    }
+}
+
+#[test]
+fn test_encryption_presets() {
+    let qpdf = load_pdf();
+    assert!(qpdf.get_permissions().modify_all);
+
+    let mem = qpdf
+        .writer()
+        .encryption_params(EncryptionParams::aes256_print_only("user", "owner"))
+        .write_to_memory()
+        .unwrap();
+    let copy = QPdf::read_from_memory_encrypted(&mem, "user").unwrap();
+    assert!(copy.is_encrypted());
+    let permissions = copy.get_permissions();
+    assert!(permissions.print_high_res);
+    assert!(permissions.print_low_res);
+    assert!(!permissions.modify_other);
+    assert!(!permissions.modify_all);
+    assert!(!permissions.extract_all);
+
+    let mem = qpdf
+        .writer()
+        .encryption_params(EncryptionParams::aes256_all_denied("user", "owner"))
+        .write_to_memory()
+        .unwrap();
+    let permissions = QPdf::read_from_memory_encrypted(&mem, "user")
+        .unwrap()
+        .get_permissions();
+    assert!(!permissions.print_low_res);
+    assert!(!permissions.modify_all);
+
+    assert!(matches!(
+        EncryptionParams::rc4_legacy("user", "owner"),
+        EncryptionParams::R3(EncryptionParamsR3 {
+            allow_print: PrintPermission::Full,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_resolve_destination() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(1).unwrap();
+
+    let dest = qpdf.new_array_from([
+        QPdfObject::from(page),
+        qpdf.new_name("/XYZ"),
+        qpdf.new_integer(72).into(),
+        qpdf.new_real(700.5, 1).into(),
+        qpdf.new_null(),
+    ]);
+    let expected = Destination {
+        page_index: 1,
+        fit: "/XYZ".to_owned(),
+        params: vec![Some(72.0), Some(700.5), None],
+    };
+    assert_eq!(qpdf.resolve_destination(dest.as_object()), Some(expected.clone()));
+
+    let items = qpdf.new_array_from([qpdf.new_string("chapter2"), dest.into_indirect()]);
+    let dests = qpdf.new_dictionary_from([("/Names", items)]);
+    let names = qpdf.new_dictionary_from([("/Dests", dests.into_indirect())]);
+    qpdf.get_root().unwrap().set("/Names", names);
+
+    assert_eq!(qpdf.resolve_destination(&qpdf.new_string("chapter2")), Some(expected));
+    assert_eq!(qpdf.resolve_destination(&qpdf.new_string("missing")), None);
+
+    let fit = qpdf.parse_object("[0 /Fit]").unwrap();
+    assert_eq!(qpdf.resolve_destination(&fit).unwrap().page_index, 0);
+}
+
+#[test]
+fn test_add_outline() {
+    let qpdf = load_pdf();
+    let intro = qpdf.add_outline("Introduction", 0, None).unwrap();
+    qpdf.add_outline("Details", 1, Some(&intro)).unwrap();
+    qpdf.add_outline("Summary", 1, None).unwrap();
+    assert!(qpdf.add_outline("Missing", 100, None).is_err());
+
+    let mem = qpdf.writer().write_to_memory().unwrap();
+    let copy = QPdf::read_from_memory(mem).unwrap();
+
+    let outlines = copy.get_outlines();
+    assert_eq!(
+        outlines.iter().map(|item| item.title()).collect::<Vec<_>>(),
+        vec!["Introduction", "Summary"]
+    );
+    let children = outlines[0].children();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].title(), "Details");
+    assert!(outlines[1].children().is_empty());
+
+    let root: QPdfDictionary = copy.get_root().unwrap().get("/Outlines").unwrap().into();
+    assert_eq!(root.get("/Count").unwrap().as_number(), Some(3.0));
+    assert_eq!(root.get("/Last").unwrap().get_id(), outlines[1].dictionary().get_id());
+    assert_eq!(outlines[0].dictionary().get("/Count").unwrap().as_number(), Some(1.0));
+    assert_eq!(
+        outlines[0].dictionary().get("/Next").unwrap().get_id(),
+        outlines[1].dictionary().get_id()
+    );
+    assert_eq!(
+        outlines[1].dictionary().get("/Prev").unwrap().get_id(),
+        outlines[0].dictionary().get_id()
+    );
+    assert!(outlines[0].dictionary().get("/Prev").is_none());
+
+    let dest = copy.resolve_destination(&children[0].destination().unwrap()).unwrap();
+    assert_eq!(dest.page_index, 1);
+    assert_eq!(dest.fit, "/XYZ");
+}
+
+#[test]
+fn test_get_document_javascript() {
+    let qpdf = load_pdf();
+    assert!(qpdf.get_document_javascript().is_empty());
+
+    let action: QPdfDictionary = qpdf
+        .parse_object("<< /S /JavaScript /JS (app.alert\\('hello'\\);) >>")
+        .unwrap()
+        .into();
+    let script = qpdf.new_stream(b"this.print();");
+    let next = qpdf.new_dictionary_from([("/S", qpdf.new_name("/JavaScript")), ("/JS", script.into_indirect())]);
+    action.set("/Next", &next);
+
+    let items = qpdf.new_array_from([qpdf.new_string("init"), action.into_indirect()]);
+    let javascript = qpdf.new_dictionary_from([("/Names", items)]);
+    let names = qpdf.new_dictionary_from([("/JavaScript", javascript.into_indirect())]);
+    let root = qpdf.get_root().unwrap();
+    root.set("/Names", names);
+    root.set(
+        "/OpenAction",
+        qpdf.parse_object("<< /S /JavaScript /JS (console.println\\(1\\);) >>")
+            .unwrap(),
+    );
+
+    assert_eq!(
+        qpdf.get_document_javascript(),
+        vec!["app.alert('hello');", "this.print();", "console.println(1);"]
+    );
+}
+
+#[test]
+fn test_remove_javascript() {
+    let qpdf = load_pdf();
+    let root = qpdf.get_root().unwrap();
+
+    let action = qpdf
+        .parse_object("<< /S /JavaScript /JS (app.alert\\(1\\);) >>")
+        .unwrap();
+    let items = qpdf.new_array_from([qpdf.new_string("init"), action.into_indirect()]);
+    let javascript = qpdf.new_dictionary_from([("/Names", items)]);
+    root.set("/Names", qpdf.new_dictionary_from([("/JavaScript", javascript)]));
+
+    let open_action: QPdfDictionary = qpdf
+        .parse_object("<< /S /GoTo /D [0 /Fit] /Next << /S /JavaScript /JS (this.print\\(\\);) >> >>")
+        .unwrap()
+        .into();
+    root.set("/OpenAction", &open_action);
+    root.set(
+        "/AA",
+        qpdf.parse_object("<< /WC << /S /JavaScript /JS (1;) >> >>").unwrap(),
+    );
+
+    let page = qpdf.get_page(0).unwrap();
+    page.set(
+        "/AA",
+        qpdf.parse_object("<< /O << /S /JavaScript /JS (2;) >> /C << /S /Named /N /NextPage >> >>")
+            .unwrap(),
+    );
+    assert_eq!(qpdf.get_document_javascript().len(), 2);
+
+    qpdf.remove_javascript().unwrap();
+
+    assert!(qpdf.get_document_javascript().is_empty());
+    let open_action: QPdfDictionary = root.get("/OpenAction").unwrap().into();
+    assert_eq!(open_action.get("/S").unwrap().as_name(), "/GoTo");
+    assert!(open_action.get("/Next").is_none());
+    assert!(root.get("/AA").is_none());
+    let page_actions: QPdfDictionary = page.get("/AA").unwrap().into();
+    assert_eq!(page_actions.keys(), vec!["/C"]);
+}
+
+#[test]
+fn test_get_fonts() {
+    let qpdf = QPdf::empty();
+    let font = qpdf
+        .parse_object("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+        .unwrap()
+        .into_indirect();
+    for _ in 0..2 {
+        let rfont = qpdf.new_dictionary_from([("/F1", font.clone())]);
+        let resources = qpdf.new_dictionary_from([("/Font", rfont)]);
+        let page = qpdf.new_dictionary_from([
+            ("/Type", qpdf.new_name("/Page")),
+            ("/MediaBox", qpdf.parse_object("[0 0 612 792]").unwrap()),
+            (
+                "/Contents",
+                qpdf.new_stream(b"BT /F1 15 Tf 72 720 Td (First Page) Tj ET\n").into(),
+            ),
+            ("/Resources", resources.into()),
+        ]);
+        qpdf.add_page(page.into_indirect(), false).unwrap();
+    }
+
+    assert_eq!(
+        qpdf.get_fonts().unwrap(),
+        vec![FontInfo {
+            base_font: Some("/Helvetica".to_owned()),
+            subtype: Some("/Type1".to_owned()),
+            embedded: false,
+            subset: false,
+        }]
+    );
+}
+
+#[test]
+fn test_parse_content() {
+    let qpdf = QPdf::empty();
+    let operators = qpdf.parse_content(b"1 0 0 1 72 720 cm /F1 12 Tf").unwrap();
+    assert_eq!(operators.len(), 2);
+    assert_eq!(operators[0].operator, "cm");
+    assert_eq!(operators[0].operands.len(), 6);
+    assert_eq!(operators[0].operands[5].as_number(), Some(720.0));
+    assert_eq!(operators[1].operator, "Tf");
+    assert_eq!(operators[1].operands.len(), 2);
+    assert_eq!(operators[1].operands[0].as_name(), "/F1");
+
+    let operators = qpdf
+        .parse_content(b"BT [(A\\(b\\)) -250 <e9>] TJ ET BI /W 1 /H 1 /BPC 8 /CS /G ID \x80 EI Q")
+        .unwrap();
+    assert_eq!(
+        operators.iter().map(|op| op.operator.as_str()).collect::<Vec<_>>(),
+        vec!["BT", "TJ", "ET", "BI", "Q"]
+    );
+    let array: QPdfArray = operators[1].operands[0].clone().into();
+    assert_eq!(array.get(0).unwrap().as_binary_string(), b"A(b)");
+    assert_eq!(array.get(2).unwrap().as_binary_string(), vec![0xe9]);
+    assert!(operators[3].operands[0].is_dictionary());
+    assert_eq!(operators[3].operands[1].as_binary_string(), vec![0x80]);
+
+    assert!(qpdf.parse_content(b"1 0 0").is_err());
+}
+
+#[test]
+fn test_rewrite_page_content() {
+    let qpdf = QPdf::empty();
+    let font = qpdf
+        .parse_object("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+        .unwrap();
+    let rfont = qpdf.new_dictionary_from([("/F1", font.into_indirect())]);
+    let resources = qpdf.new_dictionary_from([("/Font", rfont)]);
+    let mut content = ContentBuilder::new();
+    content
+        .rectangle(10.0, 10.0, 100.0, 50.0)
+        .fill()
+        .begin_text()
+        .set_font("F1", 15.0)
+        .move_text(72.0, 720.0)
+        .show_text("First (Page)")
+        .end_text();
+    let page = qpdf.new_dictionary_from([
+        ("/Type", qpdf.new_name("/Page")),
+        ("/MediaBox", qpdf.parse_object("[0 0 612 792]").unwrap()),
+        ("/Contents", qpdf.new_stream(content.build()).into()),
+        ("/Resources", resources.into()),
+    ]);
+    qpdf.add_page(page.into_indirect(), false).unwrap();
+    let page = qpdf.get_page(0).unwrap();
+    assert_eq!(qpdf.get_page_text(&page).unwrap(), "First (Page)");
+
+    // keep everything first to check that the content survives the round trip
+    qpdf.rewrite_page_content(&page, |op| vec![op]).unwrap();
+    assert_eq!(qpdf.get_page_text(&page).unwrap(), "First (Page)");
+
+    qpdf.rewrite_page_content(&page, |op| if op.operator == "Tj" { vec![] } else { vec![op] })
+        .unwrap();
+    assert_eq!(qpdf.get_page_text(&page).unwrap(), "");
+
+    let data = page.get_page_content_data().unwrap();
+    let operators = qpdf.parse_content(&data).unwrap();
+    assert_eq!(
+        operators.iter().map(|op| op.operator.as_str()).collect::<Vec<_>>(),
+        vec!["re", "f", "BT", "Tf", "Td", "ET"]
+    );
+    assert_eq!(operators[3].operands[0].as_name(), "/F1");
+}
+
+#[test]
+fn test_read_from_memory_outlives_buffer() {
+    let buffer = std::fs::read("tests/data/test.pdf").unwrap();
+    let qpdf = QPdf::read_from_memory(&buffer).unwrap();
+    drop(buffer);
+    let page = qpdf.get_page(0).unwrap();
+    assert!(!page.get_page_content_data().unwrap().is_empty());
+}
+
+#[test]
+fn test_get_structure_tree() {
+    let qpdf = load_pdf();
+    assert!(qpdf.get_structure_tree().is_none());
+
+    let page = qpdf.get_page(0).unwrap();
+    let root: QPdfDictionary = qpdf
+        .parse_object("<< /Type /StructTreeRoot >>")
+        .unwrap()
+        .into_indirect()
+        .into();
+    let document: QPdfDictionary = qpdf
+        .parse_object("<< /Type /StructElem /S /Document >>")
+        .unwrap()
+        .into_indirect()
+        .into();
+    let heading = qpdf.parse_object("<< /Type /StructElem /S /H1 /K 0 >>").unwrap();
+    let paragraph = qpdf
+        .parse_object("<< /Type /StructElem /S /P /K [1 << /Type /MCR /MCID 2 >>] >>")
+        .unwrap();
+    let figure = qpdf
+        .parse_object("<< /Type /StructElem /S /Figure /Alt (Company logo) /K 3 >>")
+        .unwrap();
+    for element in [&heading, &paragraph, &figure] {
+        QPdfDictionary::from(element.clone()).set("/P", &document);
+        QPdfDictionary::from(element.clone()).set("/Pg", &page);
+    }
+    document.set("/K", qpdf.new_array_from([heading, paragraph, figure]));
+    document.set("/P", &root);
+    root.set("/K", &document);
+    qpdf.get_root().unwrap().set("/StructTreeRoot", &root);
+
+    let tree = qpdf.get_structure_tree().unwrap();
+    assert_eq!(tree.tag, "/StructTreeRoot");
+    assert_eq!(tree.children.len(), 1);
+    let document = &tree.children[0];
+    assert_eq!(document.tag, "/Document");
+    assert_eq!(
+        document.children.iter().map(|e| e.tag.as_str()).collect::<Vec<_>>(),
+        vec!["/H1", "/P", "/Figure"]
+    );
+    assert_eq!(document.children[0].mcids, vec![0]);
+    assert_eq!(document.children[1].mcids, vec![1, 2]);
+    assert_eq!(document.children[2].alt.as_deref(), Some("Company logo"));
+}
+
+#[test]
+fn test_set_need_appearances() {
+    let qpdf = QPdf::empty();
+    qpdf.set_need_appearances(true).unwrap();
+
+    let acro_form: QPdfDictionary = qpdf.get_root().unwrap().get("/AcroForm").unwrap().into();
+    assert!(acro_form.get("/NeedAppearances").unwrap().as_bool());
+    assert!(acro_form.get("/Fields").unwrap().is_array());
+
+    qpdf.set_need_appearances(false).unwrap();
+    let acro_form: QPdfDictionary = qpdf.get_root().unwrap().get("/AcroForm").unwrap().into();
+    assert!(!acro_form.get("/NeedAppearances").unwrap().as_bool());
+}
+
+#[test]
+fn test_extract_pages() {
+    let qpdf = load_pdf();
+    let num_pages = qpdf.get_num_pages().unwrap();
+
+    let extracted = qpdf.extract_pages(1..2).unwrap();
+    assert_eq!(extracted.get_num_pages().unwrap(), 1);
+    assert_eq!(
+        extracted.get_page(0).unwrap().get_page_content_data().unwrap().as_ref(),
+        qpdf.get_page(1).unwrap().get_page_content_data().unwrap().as_ref()
+    );
+
+    let mem = extracted.writer().write_to_memory().unwrap();
+    assert_eq!(QPdf::read_from_memory(mem).unwrap().get_num_pages().unwrap(), 1);
+
+    assert_eq!(qpdf.extract_pages(0..100).unwrap().get_num_pages().unwrap(), num_pages);
+    assert_eq!(qpdf.extract_pages(100..200).unwrap().get_num_pages().unwrap(), 0);
+}
+
+#[test]
+fn test_get_page_thumbnail() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    assert!(qpdf.get_page_thumbnail(&page).is_none());
+
+    let thumb = qpdf.new_stream_with_dictionary(
+        [
+            ("/Width", qpdf.new_integer(8).into()),
+            ("/Height", qpdf.new_integer(6).into()),
+            ("/BitsPerComponent", qpdf.new_integer(8).into()),
+            ("/ColorSpace", qpdf.new_name("/DeviceRGB")),
+        ],
+        vec![0u8; 8 * 6 * 3],
+    );
+    page.set("/Thumb", thumb.into_indirect());
+
+    let mem = qpdf.writer().write_to_memory().unwrap();
+    let copy = QPdf::read_from_memory(mem).unwrap();
+    let thumbnail = copy.get_page_thumbnail(&copy.get_page(0).unwrap()).unwrap();
+    assert_eq!(thumbnail.name(), "/Thumb");
+    assert_eq!(thumbnail.width(), Some(8));
+    assert_eq!(thumbnail.height(), Some(6));
+    assert_eq!(thumbnail.color_space().as_deref(), Some("/DeviceRGB"));
+    assert_eq!(thumbnail.get_data().unwrap().len(), 8 * 6 * 3);
+    assert!(copy.get_page_thumbnail(&copy.get_page(1).unwrap()).is_none());
+}
+
+#[test]
+fn test_write_atomic() {
+    let qpdf = load_pdf();
+    let dir = std::env::temp_dir().join("qpdf_rs_write_atomic");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("output.pdf");
+    std::fs::write(&path, b"old contents").unwrap();
+
+    qpdf.writer().write_atomic(&path).unwrap();
+
+    let written = QPdf::read(&path).unwrap();
+    assert_eq!(written.get_num_pages().unwrap(), qpdf.get_num_pages().unwrap());
+    let files = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(files, vec!["output.pdf"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(qpdf.writer().write_atomic(&path).is_err());
+}
+
+#[test]
+fn test_page_content_length() {
+    let qpdf = load_pdf();
+    for page in qpdf.get_pages().unwrap() {
+        let length = qpdf.page_content_length(&page).unwrap();
+        assert!(length > 0);
+        assert_eq!(length, page.get_page_content_data().unwrap().len());
+    }
+}
+
+#[test]
+fn test_new_integer_u64() {
+    let qpdf = QPdf::empty();
+    let max = qpdf.new_integer_u64(i64::MAX as u64).unwrap();
+    assert_eq!(max.as_u64(), i64::MAX as u64);
+    assert_eq!(qpdf.new_integer_u64(42).unwrap().as_u64(), 42);
+
+    let err = qpdf.new_integer_u64(u64::MAX).err().unwrap();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+}
+
+#[test]
+fn test_is_pdf_bytes() {
+    let data = std::fs::read("tests/data/test.pdf").unwrap();
+    assert!(QPdf::is_pdf_bytes(&data));
+
+    let mut junk = b"junk before the header\n".to_vec();
+    junk.extend_from_slice(&data);
+    assert!(QPdf::is_pdf_bytes(&junk));
+    assert!(QPdf::read_from_memory(&junk).is_ok());
+
+    assert!(!QPdf::is_pdf_bytes(b"GIF89a garbage"));
+    assert!(!QPdf::is_pdf_bytes(b""));
+    assert!(!QPdf::is_pdf_bytes(&data[..data.len() / 2]));
+
+    let mut far = vec![b' '; 2048];
+    far.extend_from_slice(&data);
+    assert!(!QPdf::is_pdf_bytes(&far));
+}
+
+#[test]
+fn test_object_streams_require_pdf_1_5() {
+    let qpdf = load_pdf();
+    let err = qpdf
+        .writer()
+        .force_pdf_version("1.4")
+        .object_stream_mode(ObjectStreamMode::Generate)
+        .write_to_memory()
+        .unwrap_err();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+
+    assert!(qpdf
+        .writer()
+        .force_pdf_version("1.4")
+        .object_stream_mode(ObjectStreamMode::Disable)
+        .write_to_memory()
+        .is_ok());
+    assert!(qpdf
+        .writer()
+        .force_pdf_version("1.5")
+        .object_stream_mode(ObjectStreamMode::Generate)
+        .write_to_memory()
+        .is_ok());
+}
+
+#[test]
+fn test_open_action() {
+    let qpdf = load_pdf();
+    assert!(qpdf.get_open_action().is_none());
+
+    qpdf.set_open_action_goto(0, Fit::FitPage).unwrap();
+    let action: QPdfDictionary = qpdf.get_open_action().unwrap().into();
+    assert_eq!(action.get("/S").unwrap().as_name(), "/GoTo");
+    let dest = qpdf.resolve_destination(action.as_object()).unwrap();
+    assert_eq!(dest.page_index, 0);
+    assert_eq!(dest.fit, "/Fit");
+    assert!(dest.params.is_empty());
+
+    qpdf.set_open_action_goto(
+        1,
+        Fit::XYZ {
+            x: 0.0,
+            y: 792.0,
+            zoom: 1.5,
+        },
+    )
+    .unwrap();
+    let dest = qpdf.resolve_destination(&qpdf.get_open_action().unwrap()).unwrap();
+    assert_eq!(dest.page_index, 1);
+    assert_eq!(dest.fit, "/XYZ");
+    assert_eq!(dest.params, vec![Some(0.0), Some(792.0), Some(1.5)]);
+
+    assert!(qpdf.set_open_action_goto(100, Fit::FitWidth).is_err());
+}
+
+#[test]
+fn test_open_action_shortcuts() {
+    let qpdf = load_pdf();
+
+    qpdf.open_at_page(1).unwrap();
+    let dest = qpdf.resolve_destination(&qpdf.get_open_action().unwrap()).unwrap();
+    assert_eq!(dest.page_index, 1);
+    assert_eq!(dest.fit, "/Fit");
+
+    qpdf.open_fit_width().unwrap();
+    let dest = qpdf.resolve_destination(&qpdf.get_open_action().unwrap()).unwrap();
+    assert_eq!(dest.page_index, 0);
+    assert_eq!(dest.fit, "/FitH");
+    assert_eq!(dest.params.len(), 1);
+
+    assert!(qpdf.open_at_page(100).is_err());
+}
+
+#[test]
+fn test_page_layout_and_mode() {
+    let qpdf = load_pdf();
+
+    qpdf.set_page_mode(PageMode::UseOutlines).unwrap();
+    let root = qpdf.get_root().unwrap();
+    assert_eq!(root.get("/PageMode").unwrap().as_name(), "/UseOutlines");
+    assert_eq!(qpdf.get_page_mode(), Some(PageMode::UseOutlines));
+
+    qpdf.set_page_layout(PageLayout::TwoColumnLeft).unwrap();
+    assert_eq!(root.get("/PageLayout").unwrap().as_name(), "/TwoColumnLeft");
+    assert_eq!(qpdf.get_page_layout(), Some(PageLayout::TwoColumnLeft));
+    assert_eq!(qpdf.get_two_page_spread(), Some(false));
+
+    root.set("/PageMode", qpdf.new_name("/Unknown"));
+    assert_eq!(qpdf.get_page_mode(), None);
+}
+
+#[test]
+fn test_checked_conversions() {
+    let qpdf = QPdf::empty();
+    let dict = qpdf.parse_object("<< /Key [1 2] >>").unwrap();
+    assert!(dict.as_stream().is_none());
+    assert!(dict.as_array().is_none());
+    assert!(dict.as_scalar().is_none());
+    let dict = dict.as_dictionary().unwrap();
+    assert!(dict.has("/Key"));
+
+    let array = dict.get("/Key").unwrap().as_array().unwrap();
+    assert_eq!(array.len(), 2);
+    assert_eq!(array.get(1).unwrap().as_scalar().unwrap().as_i64(), 2);
+
+    let stream = qpdf.new_stream(b"data");
+    let stream = stream.as_object().as_stream().unwrap();
+    assert_eq!(stream.get_data(StreamDecodeLevel::None).unwrap().as_ref(), b"data");
+    assert!(stream.as_dictionary().is_none());
+}
+
+#[test]
+fn test_page_as_form_xobject() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let form = qpdf.page_as_form_xobject(&page).unwrap();
+    let dict = form.get_dictionary();
+    assert_eq!(dict.get("/Type").unwrap().as_name(), "/XObject");
+    assert_eq!(dict.get("/Subtype").unwrap().as_name(), "/Form");
+    assert_eq!(dict.get_rect("/BBox"), qpdf.get_page_boxes(&page).effective_crop_box());
+    assert!(dict.get("/Resources").unwrap().is_dictionary());
+    assert_eq!(
+        QPdfArray::from(dict.get("/Matrix").unwrap()).as_f64_vec(),
+        Some(vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    );
+    assert_eq!(
+        form.get_data(StreamDecodeLevel::None).unwrap().as_ref(),
+        page.get_page_content_data().unwrap().as_ref()
+    );
+
+    page.set("/Rotate", qpdf.new_integer(90));
+    page.set_rect("/MediaBox", [0.0, 0.0, 612.0, 792.0]);
+    page.remove("/CropBox");
+    let form = qpdf.page_as_form_xobject(&page).unwrap();
+    assert_eq!(
+        QPdfArray::from(form.get_dictionary().get("/Matrix").unwrap()).as_f64_vec(),
+        Some(vec![0.0, -1.0, 1.0, 0.0, 0.0, 612.0])
+    );
+}
+
+#[test]
+fn test_page_as_form_xobject_inherited_attributes() {
+    let source = QPdf::empty();
+    let page = source.add_blank_page(612.0, 792.0, false).unwrap();
+    page.remove("/Resources");
+    let pages: QPdfDictionary = source.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/Rotate", source.new_integer(180));
+    pages.set("/Resources", source.parse_object("<< /ProcSet [/PDF] >>").unwrap());
+
+    let qpdf = QPdf::empty();
+    let form = qpdf.page_as_form_xobject(&page).unwrap();
+    let dict = form.get_dictionary();
+    assert!(dict.get("/Resources").unwrap().is_dictionary());
+    assert_eq!(
+        QPdfArray::from(dict.get("/Matrix").unwrap()).as_f64_vec(),
+        Some(vec![-1.0, 0.0, 0.0, -1.0, 612.0, 792.0])
+    );
+    assert!(page.get("/Resources").is_none());
+    assert!(page.get("/Rotate").is_none());
+}
+
+#[test]
+fn test_draw_xobject() {
+    let qpdf = load_pdf();
+    let page = qpdf.get_page(0).unwrap();
+    let form = qpdf.page_as_form_xobject(&qpdf.get_page(1).unwrap()).unwrap();
+
+    // the hinted name is taken by another object, so a numbered one is used
+    let other = qpdf.new_stream(b"").into_indirect();
+    let resources: QPdfDictionary = page
+        .get_or_insert_with("/Resources", || qpdf.new_dictionary().into())
+        .into();
+    let xobjects: QPdfDictionary = resources
+        .get_or_insert_with("/XObject", || qpdf.new_dictionary().into())
+        .into();
+    xobjects.set("/Stamp", &other);
+
+    qpdf.draw_xobject(&page, &form, [0.5, 0.0, 0.0, 0.5, 100.0, 200.0], "Stamp")
+        .unwrap();
+
+    let xobjects: QPdfDictionary = QPdfDictionary::from(page.get("/Resources").unwrap())
+        .get("/XObject")
+        .unwrap()
+        .into();
+    assert_eq!(xobjects.get("/Stamp").unwrap().get_id(), other.get_id());
+    let stamp = xobjects.get("/Stamp1").unwrap();
+    assert!(stamp.is_indirect() && stamp.is_stream());
+
+    let operators = qpdf.parse_content(&page.get_page_content_data().unwrap()).unwrap();
+    let draw = operators.iter().position(|op| op.operator == "Do").unwrap();
+    assert_eq!(operators[draw].operands[0].as_name(), "/Stamp1");
+    assert_eq!(operators[draw - 1].operator, "cm");
+    assert_eq!(
+        operators[draw - 1]
+            .operands
+            .iter()
+            .map(|v| v.as_number().unwrap())
+            .collect::<Vec<_>>(),
+        vec![0.5, 0.0, 0.0, 0.5, 100.0, 200.0]
+    );
+}
+
+#[test]
+fn test_draw_xobject_inherited_resources() {
+    let qpdf = QPdf::empty();
+    let page = qpdf.add_blank_page(612.0, 792.0, false).unwrap();
+    page.remove("/Resources");
+    let pages: QPdfDictionary = qpdf.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/Resources", qpdf.parse_object("<< /ProcSet [/PDF] >>").unwrap());
+
+    let stamp = qpdf.new_stream(b"");
+    qpdf.draw_xobject(&page, &stamp, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0], "Stamp")
+        .unwrap();
+
+    let resources: QPdfDictionary = page.get("/Resources").unwrap().into();
+    assert!(resources.get("/ProcSet").is_some());
+    assert!(QPdfDictionary::from(resources.get("/XObject").unwrap())
+        .get("/Stamp")
+        .is_some());
+    let shared: QPdfDictionary = pages.get("/Resources").unwrap().into();
+    assert!(shared.get("/XObject").is_none());
+}
+
+#[test]
+fn test_page_count_hint() {
+    let qpdf = load_pdf();
+    assert_eq!(qpdf.page_count_hint(), Some(qpdf.get_num_pages().unwrap()));
+
+    let pages: QPdfDictionary = qpdf.get_root().unwrap().get("/Pages").unwrap().into();
+    pages.set("/Count", qpdf.new_integer(-1));
+    assert_eq!(qpdf.page_count_hint(), None);
+    pages.remove("/Count");
+    assert_eq!(qpdf.page_count_hint(), None);
+}
+
+#[test]
+fn test_error_source() {
+    let qpdf = QPdf::empty();
+    let err = qpdf.parse_object("/a\0b").err().unwrap();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+    let source = std::error::Error::source(&err).unwrap();
+    assert!(source.downcast_ref::<std::ffi::NulError>().is_some());
+
+    let err = QPdf::read("/nonexistent/file.pdf").err().unwrap();
+    assert!(std::error::Error::source(&err).is_none());
+}
+
+#[test]
+fn test_string_constructors_with_null() {
+    let qpdf = QPdf::empty();
+
+    let err = qpdf.try_new_name("/a\0b").err().unwrap();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+    let err = qpdf.try_new_utf8_string("a\0b").err().unwrap();
+    assert_eq!(err.error_code(), QPdfErrorCode::InvalidParameter);
+
+    assert_eq!(qpdf.try_new_name("/Name").unwrap().as_name(), "/Name");
+    assert_eq!(qpdf.try_new_utf8_string("text").unwrap().as_string(), "text");
+
+    let string = qpdf.new_string("a\0b");
+    assert!(string.is_string());
+    assert_eq!(string.as_binary_string(), b"a\0b");
+    assert_eq!(qpdf.new_binary_string(b"a\0b").as_binary_string(), b"a\0b");
+}
+
+#[test]
+fn test_as_raw_string() {
+    let qpdf = QPdf::empty();
+
+    // PDFDocEncoding bytes which are not valid UTF-8
+    let bytes = b"\xe9t\xe9 \x80";
+    let string = qpdf.new_binary_string(bytes);
+    assert_eq!(string.as_raw_string(), bytes);
+    assert_eq!(string.as_string(), "\u{e9}t\u{e9} \u{2022}");
+    assert_ne!(string.as_string().as_bytes(), bytes);
+}
+
+#[test]
+fn test_report_font_usage() {
+    let qpdf = QPdf::empty();
+    let font = qpdf
+        .parse_object("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+        .unwrap()
+        .into_indirect();
+    let unnamed = qpdf.parse_object("<< /Type /Font /Subtype /Type3 >>").unwrap();
+    let contents: [&[u8]; 2] = [
+        b"BT /F1 15 Tf 72 720 Td (First Page) Tj ET\n",
+        b"BT /F1 15 Tf [(Pa) -500 (ge)] TJ /F2 10 Tf <0102> Tj ET\n",
+    ];
+    for content in contents {
+        let rfont = qpdf.new_dictionary_from([("/F1", font.clone()), ("/F2", unnamed.clone())]);
+        let resources = qpdf.new_dictionary_from([("/Font", rfont)]);
+        let page = qpdf.new_dictionary_from([
+            ("/Type", qpdf.new_name("/Page")),
+            ("/MediaBox", qpdf.parse_object("[0 0 612 792]").unwrap()),
+            ("/Contents", qpdf.new_stream(content).into()),
+            ("/Resources", resources.into()),
+        ]);
+        qpdf.add_page(page.into_indirect(), false).unwrap();
+    }
+
+    let used = "First Page".chars().collect::<HashSet<_>>().len();
+    assert_eq!(
+        qpdf.report_font_usage().unwrap(),
+        vec![("/Helvetica".to_owned(), used), ("/F2".to_owned(), 2)]
+    );
+}